@@ -9,40 +9,226 @@
 ///
 /// assert_eq!(algebra::binary_exponentiation::binary_exponentiation(a, n), 1594323);
 /// ```
-pub fn binary_exponentiation(mut a: u64, mut n: u64) -> u64 {
-    let mut res = 1;
-    while n != 0 && a != 0 {
-        if n & 1 == 1 {
-            res *= a;
-        }
-        a *= a;
-        n >>= 1;
-    }
-
-    res
+pub fn binary_exponentiation(a: u64, n: u64) -> u64 {
+    pow(&Multiplication, &a, n)
 }
 
 /// Calculates a^n % m
 ///
 /// [CP Algos](https://cp-algorithms.com/algebra/binary-exp.html)
 ///
+/// 0^0 is taken to be 1 and 0^n is 0 for n >= 1, both reduced mod m, so m = 1 always gives 0.
+///
 /// ```
 /// let a = 4;
 /// let n = 56;
 /// let m = 379;
 ///
 /// assert_eq!(algebra::binary_exponentiation::binary_exponentiation_mod(a, n, m), 239);
+///
+/// assert_eq!(algebra::binary_exponentiation::binary_exponentiation_mod(0, 5, 7), 0);
+/// assert_eq!(algebra::binary_exponentiation::binary_exponentiation_mod(0, 0, 7), 1);
+/// assert_eq!(algebra::binary_exponentiation::binary_exponentiation_mod(3, 0, 1), 0);
+///
+/// // Products are taken in 128 bits, so any 64 bit modulus works
+/// let m = 18446744073709551557;
+/// assert_eq!(algebra::binary_exponentiation::binary_exponentiation_mod(2, m - 1, m), 1);
+/// ```
+pub fn binary_exponentiation_mod(a: u64, n: u64, m: u64) -> u64 {
+    pow(&MultiplicationMod::new(m), &(a % m), n)
+}
+
+/// Calculates a * b % m without overflowing for any 64 bit modulus
+///
+/// ```
+/// let m = 18446744073709551557;
+///
+/// assert_eq!(algebra::binary_exponentiation::mul_mod(m - 1, m - 1, m), 1);
+/// ```
+pub fn mul_mod(a: u64, b: u64, m: u64) -> u64 {
+    ((a as u128 * b as u128) % m as u128) as u64
+}
+
+/// A set with an associative operation and an identity element
+///
+/// The monoid is a value so it can carry a modulus or a size, its elements are [`Self::Elem`].
+pub trait Monoid {
+    type Elem: Clone;
+    fn identity(&self) -> Self::Elem;
+    /// Associative, but not necessarily commutative
+    fn op(&self, a: &Self::Elem, b: &Self::Elem) -> Self::Elem;
+}
+
+/// a^n, the n-fold product of a with itself, in O(log n) operations
+///
+/// a^0 is the identity. Only powers of a are multiplied together, and those commute,
+/// so the result is the same for non-commutative monoids.
 /// ```
-pub fn binary_exponentiation_mod(mut a: u64, mut n: u64, m: u64) -> u64 {
-    a %= m;
-    let mut res = 1;
-    while n != 0 && a != 0 {
+/// use algebra::binary_exponentiation::{pow, AffineMaps, Permutations};
+///
+/// // Applying a 3-cycle 10 times is applying it once
+/// let cycle = vec![1, 2, 0, 3];
+/// assert_eq!(pow(&Permutations::new(4), &cycle, 10), cycle);
+///
+/// // x -> 2x + 1 applied 10 times maps 0 to 2^10 - 1
+/// assert_eq!(pow(&AffineMaps::new(1000000007), &(2, 1), 10), (1024, 1023));
+/// ```
+pub fn pow<M: Monoid>(monoid: &M, a: &M::Elem, mut n: u64) -> M::Elem {
+    let mut res = monoid.identity();
+    let mut a = a.clone();
+    while n != 0 {
         if n & 1 == 1 {
-            res = res * a % m;
+            res = monoid.op(&res, &a);
         }
-        a = a * a % m;
         n >>= 1;
+        // Skipping the last squaring keeps a^n from overflowing when it fits
+        if n != 0 {
+            a = monoid.op(&a, &a);
+        }
     }
 
     res
 }
+
+/// u64 under multiplication, overflowing as u64 does
+pub struct Multiplication;
+
+impl Monoid for Multiplication {
+    type Elem = u64;
+
+    fn identity(&self) -> u64 {
+        1
+    }
+
+    fn op(&self, a: &u64, b: &u64) -> u64 {
+        a * b
+    }
+}
+
+/// Residues mod m under multiplication
+pub struct MultiplicationMod {
+    m: u64,
+}
+
+impl MultiplicationMod {
+    pub fn new(m: u64) -> MultiplicationMod {
+        MultiplicationMod { m }
+    }
+}
+
+impl Monoid for MultiplicationMod {
+    type Elem = u64;
+
+    fn identity(&self) -> u64 {
+        1 % self.m
+    }
+
+    fn op(&self, a: &u64, b: &u64) -> u64 {
+        mul_mod(*a, *b, self.m)
+    }
+}
+
+/// k x k matrices mod m under multiplication, as rows
+/// ```
+/// use algebra::binary_exponentiation::{pow, Matrices};
+///
+/// // [[1, 1], [1, 0]]^n holds F(n + 1), F(n) in its top row
+/// let q = vec![vec![1, 1], vec![1, 0]];
+/// assert_eq!(pow(&Matrices::new(2, 1000000007), &q, 100)[0][1], 687995182);
+/// assert_eq!(pow(&Matrices::new(2, 10), &q, 0), vec![vec![1, 0], vec![0, 1]]);
+/// ```
+pub struct Matrices {
+    k: usize,
+    m: u64,
+}
+
+impl Matrices {
+    pub fn new(k: usize, m: u64) -> Matrices {
+        Matrices { k, m }
+    }
+}
+
+impl Monoid for Matrices {
+    type Elem = Vec<Vec<u64>>;
+
+    fn identity(&self) -> Vec<Vec<u64>> {
+        let mut id = vec![vec![0; self.k]; self.k];
+        for (i, row) in id.iter_mut().enumerate() {
+            row[i] = 1 % self.m;
+        }
+
+        id
+    }
+
+    fn op(&self, a: &Vec<Vec<u64>>, b: &Vec<Vec<u64>>) -> Vec<Vec<u64>> {
+        let (k, m) = (self.k, self.m);
+        let mut c = vec![vec![0; k]; k];
+        for i in 0..k {
+            for l in 0..k {
+                if a[i][l] == 0 {
+                    continue;
+                }
+                for j in 0..k {
+                    let sum = c[i][j] as u128 + mul_mod(a[i][l], b[l][j], m) as u128;
+                    c[i][j] = (sum % m as u128) as u64;
+                }
+            }
+        }
+
+        c
+    }
+}
+
+/// Permutations of [0, k) under composition
+///
+/// A permutation maps i to p[i], and op(a, b) is a after b, mapping i to a[b[i]].
+pub struct Permutations {
+    k: usize,
+}
+
+impl Permutations {
+    pub fn new(k: usize) -> Permutations {
+        Permutations { k }
+    }
+}
+
+impl Monoid for Permutations {
+    type Elem = Vec<usize>;
+
+    fn identity(&self) -> Vec<usize> {
+        (0..self.k).collect()
+    }
+
+    fn op(&self, a: &Vec<usize>, b: &Vec<usize>) -> Vec<usize> {
+        b.iter().map(|&i| a[i]).collect()
+    }
+}
+
+/// Affine maps x -> ax + b mod m under composition, as (a, b)
+///
+/// op(f, g) is f after g.
+pub struct AffineMaps {
+    m: u64,
+}
+
+impl AffineMaps {
+    pub fn new(m: u64) -> AffineMaps {
+        AffineMaps { m }
+    }
+}
+
+impl Monoid for AffineMaps {
+    type Elem = (u64, u64);
+
+    fn identity(&self) -> (u64, u64) {
+        (1 % self.m, 0)
+    }
+
+    fn op(&self, &(a1, b1): &(u64, u64), &(a2, b2): &(u64, u64)) -> (u64, u64) {
+        let m = self.m;
+        // a1 (a2 x + b2) + b1
+        let b = ((mul_mod(a1, b2, m) as u128 + b1 as u128) % m as u128) as u64;
+
+        (mul_mod(a1, a2, m), b)
+    }
+}
@@ -0,0 +1,449 @@
+//! [Binomial Coefficients](https://cp-algorithms.com/combinatorics/binomial-coefficients.html)
+//! and related counts modulo a prime, and binomials modulo any number
+use crate::binary_exponentiation::mul_mod;
+use crate::factorizer::{AutoFactorizer, Factorizer};
+use crate::linear_congruence::solve_congruence_system;
+use crate::modular_inverse::modular_inverse_prime;
+
+/// Factorials and inverse factorials mod a prime p, up to a limit
+///
+/// Factorials from p on are all 0 mod p, so the tables stop at p - 1 whatever the limit.
+/// Built in O(n) with a single modular inverse, after which the counts are O(1).
+/// Stirling numbers need an O(n^2) table of their own, built by [`with_stirling2`](Self::with_stirling2).
+/// ```
+/// use algebra::combinatorics::Combinatorics;
+///
+/// let c = Combinatorics::with_stirling2(100, 1000000007);
+/// assert_eq!(c.binom(10, 3), 120);
+/// assert_eq!(c.binom(3, 10), 0);
+/// assert_eq!(c.binom(100, 50), 538992043);
+/// assert_eq!(c.perm(10, 3), 720);
+/// assert_eq!(c.multinomial(&[2, 3, 4]), 1260);
+/// assert_eq!(c.catalan(10), 16796);
+/// assert_eq!(c.stirling2(10, 3), 9330);
+/// ```
+pub struct Combinatorics {
+    p: u64,
+    factorial: Vec<u64>,
+    inverse_factorial: Vec<u64>,
+    stirling2: Vec<Vec<u64>>,
+}
+
+impl Combinatorics {
+    /// Tables for [0, min(n, p - 1)]
+    pub fn new(n: u64, p: u64) -> Combinatorics {
+        let limit = n.min(p - 1) as usize;
+        let mut factorial = vec![1 % p; limit + 1];
+        for i in 1..=limit {
+            factorial[i] = mul_mod(factorial[i - 1], i as u64, p);
+        }
+        let mut inverse_factorial = vec![0; limit + 1];
+        inverse_factorial[limit] = modular_inverse_prime(factorial[limit], p).unwrap();
+        // 1 / (i - 1)! = i / i!
+        for i in (1..=limit).rev() {
+            inverse_factorial[i - 1] = mul_mod(inverse_factorial[i], i as u64, p);
+        }
+
+        Combinatorics {
+            p,
+            factorial,
+            inverse_factorial,
+            stirling2: Vec::new(),
+        }
+    }
+
+    /// Tables for [0, min(n, p - 1)], plus S(i, j) for j <= i <= n in O(n^2)
+    pub fn with_stirling2(n: u64, p: u64) -> Combinatorics {
+        let mut c = Combinatorics::new(n, p);
+        // S(i, j) = j S(i - 1, j) + S(i - 1, j - 1)
+        let mut stirling2 = vec![vec![1 % p]];
+        for i in 1..=n as usize {
+            let prev = &stirling2[i - 1];
+            let mut row = vec![0; i + 1];
+            for j in 1..=i {
+                let stay = if j < i { mul_mod(j as u64, prev[j], p) } else { 0 };
+                row[j] = ((stay as u128 + prev[j - 1] as u128) % p as u128) as u64;
+            }
+            stirling2.push(row);
+        }
+        c.stirling2 = stirling2;
+
+        c
+    }
+
+    /// Largest n covered by the tables
+    pub fn limit(&self) -> u64 {
+        self.factorial.len() as u64 - 1
+    }
+
+    /// n! mod p
+    pub fn factorial(&self, n: u64) -> u64 {
+        self.factorial[n as usize]
+    }
+
+    /// (n!)^-1 mod p
+    pub fn inverse_factorial(&self, n: u64) -> u64 {
+        self.inverse_factorial[n as usize]
+    }
+
+    /// Ways to choose k of n, 0 for k > n
+    ///
+    /// # Panics
+    /// If n is above [`limit`](Self::limit), see [`binom_lucas`](Self::binom_lucas) for those
+    pub fn binom(&self, n: u64, k: u64) -> u64 {
+        if k > n {
+            return 0;
+        }
+        let p = self.p;
+
+        mul_mod(
+            self.factorial(n),
+            mul_mod(self.inverse_factorial(k), self.inverse_factorial(n - k), p),
+            p,
+        )
+    }
+
+    /// Ordered arrangements of k out of n, 0 for k > n
+    pub fn perm(&self, n: u64, k: u64) -> u64 {
+        if k > n {
+            return 0;
+        }
+
+        mul_mod(self.factorial(n), self.inverse_factorial(n - k), self.p)
+    }
+
+    /// (k_1 + ... + k_r)! / (k_1! ... k_r!)
+    pub fn multinomial(&self, ks: &[u64]) -> u64 {
+        let p = self.p;
+        ks.iter().fold(self.factorial(ks.iter().sum()), |acc, &k| {
+            mul_mod(acc, self.inverse_factorial(k), p)
+        })
+    }
+
+    /// n-th Catalan number C(2n, n) / (n + 1), needs 2n within the limit
+    pub fn catalan(&self, n: u64) -> u64 {
+        // C(2n, n) - C(2n, n + 1) avoids dividing by n + 1, which may be a multiple of p
+        (self.binom(2 * n, n) + self.p - self.binom(2 * n, n + 1)) % self.p
+    }
+
+    /// Stirling number of the second kind S(n, k), the partitions of n items into k non-empty sets
+    ///
+    /// 0 for k > n, looked up in the table of [`with_stirling2`](Self::with_stirling2).
+    ///
+    /// # Panics
+    /// If n is beyond that table, which [`new`](Self::new) leaves empty
+    /// ```
+    /// use algebra::combinatorics::Combinatorics;
+    ///
+    /// let c = Combinatorics::with_stirling2(25, 998244353);
+    /// assert_eq!(c.stirling2(0, 0), 1);
+    /// assert_eq!(c.stirling2(5, 0), 0);
+    /// assert_eq!(c.stirling2(3, 5), 0);
+    /// assert_eq!(c.stirling2(25, 5), 455768341);
+    ///
+    /// // The table goes past p, where the factorials stop
+    /// let c = Combinatorics::with_stirling2(10, 7);
+    /// assert_eq!(c.stirling2(10, 3), 9330 % 7);
+    /// ```
+    pub fn stirling2(&self, n: u64, k: u64) -> u64 {
+        if k > n {
+            return 0;
+        }
+
+        self.stirling2[n as usize][k as usize]
+    }
+
+    /// C(n, k) mod p for any n by Lucas' theorem
+    ///
+    /// C(n, k) is the product of C(n_i, k_i) over the base p digits of n and k,
+    /// which takes O(log_p n).
+    ///
+    /// # Panics
+    /// If the tables don't reach p - 1
+    /// ```
+    /// use algebra::combinatorics::Combinatorics;
+    ///
+    /// let c = Combinatorics::new(u64::MAX, 13);
+    /// assert_eq!(c.binom_lucas(100, 30), 7);
+    /// assert_eq!(c.binom_lucas(27, 14), 2);
+    /// assert_eq!(c.binom_lucas(1000, 500), 0);
+    /// assert_eq!(c.binom_lucas(2218611106740436991, 12345), 2);
+    /// ```
+    pub fn binom_lucas(&self, mut n: u64, mut k: u64) -> u64 {
+        let p = self.p;
+        assert_eq!(self.limit(), p - 1, "Lucas' theorem needs factorials up to p - 1");
+        let mut res = 1 % p;
+        while k > 0 {
+            res = mul_mod(res, self.binom(n % p, k % p), p);
+            n /= p;
+            k /= p;
+        }
+
+        res
+    }
+}
+
+/// C(n, k) mod m for any m >= 1
+///
+/// m is factored and C(n, k) is found mod each prime power p^e dividing it by Granville's
+/// generalization of Lucas' theorem: the p's are pulled out of the factorials by Legendre's formula
+/// and the rest, n! without its factors p, is a product of units mod p^e that repeats every p^e.
+/// The results are joined by the Chinese remainder theorem.
+/// Products of units over blocks of p^i are kept as polynomials of degree below e, so each prime
+/// power takes O(e^2) memory and O(min(n, p e^3)) time. Primes dividing m once go by
+/// Lucas' theorem instead, at O(min(k_i, n_i - k_i)) for each base p digit.
+/// ```
+/// use algebra::combinatorics::binom_mod;
+///
+/// assert_eq!(binom_mod(10, 3, 1000), 120);
+/// assert_eq!(binom_mod(10, 11, 1000), 0);
+/// assert_eq!(binom_mod(1000, 500, 1000000000), 821216320);
+/// assert_eq!(binom_mod(1000000, 12345, 1000000000), 584800000);
+/// assert_eq!(binom_mod(1000000, 300000, (1 << 20) * 243 * 7), 1659958272);
+/// assert_eq!(binom_mod(100, 50, 18446744073709551615), 1184508339309490851);
+/// assert_eq!(binom_mod(5, 2, 1), 0);
+///
+/// // Prime powers far bigger than n or than any table
+/// assert_eq!(binom_mod(1 << 62, 3, 1 << 63), 1 << 62);
+/// assert_eq!(binom_mod(1000000, 300000, 3u64.pow(40)), 8384326485724991673);
+///
+/// // A large prime factor costs little when k is small
+/// assert_eq!(binom_mod(1000000000, 12345, 2 * 1000000007), 463571078);
+/// ```
+pub fn binom_mod(n: u64, k: u64, m: u64) -> u64 {
+    if k > n {
+        return 0;
+    }
+
+    let congruences: Vec<(u64, u64, u64)> = AutoFactorizer::new()
+        .factor(m)
+        .factors()
+        .iter()
+        .map(|&(p, e)| match e {
+            1 => (1, binom_mod_prime(n, k, p), p),
+            _ => (1, binom_mod_prime_power(n, k, p, e) as u64, p.pow(e)),
+        })
+        .collect();
+
+    solve_congruence_system(&congruences).unwrap().0 % m
+}
+
+/// C(n, k) mod 2^64, the modulus [`binom_mod`] can't take
+/// ```
+/// use algebra::combinatorics::{binom_mod, binom_mod_2_64};
+///
+/// assert_eq!(binom_mod_2_64(10, 3), 120);
+/// assert_eq!(binom_mod_2_64(100, 50), 1184508333840160104);
+/// assert_eq!(binom_mod_2_64(1000000, 300000), 15251042122409767936);
+/// assert_eq!(binom_mod_2_64(18446744073709551615, 12345678901) % (1 << 63), binom_mod(18446744073709551615, 12345678901, 1 << 63));
+/// ```
+pub fn binom_mod_2_64(n: u64, k: u64) -> u64 {
+    if k > n {
+        return 0;
+    }
+
+    binom_mod_prime_power(n, k, 2, 64) as u64
+}
+
+/// C(n, k) mod a prime p by Lucas' theorem, each digit from n_i ... (n_i - r + 1) / r!
+/// for r = min(k_i, n_i - k_i)
+fn binom_mod_prime(mut n: u64, mut k: u64, p: u64) -> u64 {
+    let mut res = 1 % p;
+    while k > 0 {
+        let (ni, ki) = (n % p, k % p);
+        if ki > ni {
+            return 0;
+        }
+        let r = ki.min(ni - ki);
+        let (mut numerator, mut denominator) = (1 % p, 1 % p);
+        for i in 1..=r {
+            numerator = mul_mod(numerator, ni - r + i, p);
+            denominator = mul_mod(denominator, i, p);
+        }
+        res = mul_mod(res, mul_mod(numerator, modular_inverse_prime(denominator, p).unwrap(), p), p);
+        n /= p;
+        k /= p;
+    }
+
+    res
+}
+
+/// C(n, k) mod p^e for k <= n and p^e <= 2^64
+fn binom_mod_prime_power(n: u64, k: u64, p: u64, e: u32) -> u128 {
+    let v = legendre_exponent(n, p) - legendre_exponent(k, p) - legendre_exponent(n - k, p);
+    if v >= e as u64 {
+        return 0;
+    }
+
+    let units = UnitProducts::new(p, e, n);
+    let pe = units.pe;
+    let denominator = mul_mod_wide(units.factorial_without_p(k), units.factorial_without_p(n - k), pe);
+    // Units mod p^e have order dividing φ(p^e) = p^(e - 1) (p - 1)
+    let phi = pe / p as u128 * (p as u128 - 1);
+    let inverse = pow_mod_wide(denominator, phi - 1, pe);
+    let binom = mul_mod_wide(units.factorial_without_p(n), inverse, pe);
+
+    mul_mod_wide(binom, (p as u128).pow(v as u32), pe)
+}
+
+/// Exponent of p in n!, Σ n / p^i
+fn legendre_exponent(mut n: u64, p: u64) -> u64 {
+    let mut v = 0;
+    while n > 0 {
+        n /= p;
+        v += n;
+    }
+
+    v
+}
+
+/// Products of the units mod p^e in [1, x], for p^e <= 2^64
+///
+/// blocks[i](y) is the product of y + j over the units j in [1, p^(i + 1)], as a polynomial mod p^e.
+/// Only y divisible by p are plugged in, so the terms of degree e and up are multiples of p^e
+/// and are dropped. The block of p^(i + 2) is p shifted copies of the one of p^(i + 1).
+struct UnitProducts {
+    p: u128,
+    e: u32,
+    pe: u128,
+    blocks: Vec<Vec<u128>>,
+}
+
+impl UnitProducts {
+    /// Blocks for every p^i up to min(n, p^e - 1)
+    fn new(p: u64, e: u32, n: u64) -> UnitProducts {
+        let p = p as u128;
+        let pe = p.pow(e);
+        let len = e as usize;
+        // Binomials for shifting polynomials, C(d, a) mod p^e for d < e
+        let mut binom = vec![vec![0; len]; len];
+        for d in 0..len {
+            binom[d][0] = 1 % pe;
+            for a in 1..=d {
+                binom[d][a] = (binom[d - 1][a - 1] + binom[d - 1][a]) % pe;
+            }
+        }
+
+        let top = (n as u128).min(pe - 1);
+        let mut blocks: Vec<Vec<u128>> = Vec::new();
+        let mut size = p;
+        while size <= top {
+            let block = match blocks.last() {
+                None => (1..p).fold(polynomial_one(len, pe), |acc, j| {
+                    polynomial_mul(&acc, &[j % pe, 1 % pe], pe)
+                }),
+                Some(last) => {
+                    let step = size / p;
+                    (0..p).fold(polynomial_one(len, pe), |acc, t| {
+                        polynomial_mul(&acc, &polynomial_shift(last, t * step % pe, &binom, pe), pe)
+                    })
+                }
+            };
+            blocks.push(block);
+            size *= p;
+        }
+
+        UnitProducts { p, e, pe, blocks }
+    }
+
+    /// Product of the units in [1, x] mod p^e, for x < p^e
+    ///
+    /// The base p digits of x are walked from the top, each block starting where the last ended.
+    fn up_to(&self, x: u128) -> u128 {
+        let (p, pe) = (self.p, self.pe);
+        let mut res = 1 % pe;
+        let mut start = 0;
+        for (i, block) in self.blocks.iter().enumerate().rev() {
+            let size = p.pow(i as u32 + 1);
+            for _ in 0..(x - start) / size {
+                res = mul_mod_wide(res, polynomial_eval(block, start % pe, pe), pe);
+                start += size;
+            }
+        }
+        for j in start + 1..=x {
+            res = mul_mod_wide(res, j % pe, pe);
+        }
+
+        res
+    }
+
+    /// n! with every factor p removed, mod p^e
+    ///
+    /// n! / p^(n / p) (n / p)! is the product of the units up to n, and the product over a full
+    /// period of p^e is -1, or 1 for powers of 2 from 8 on.
+    fn factorial_without_p(&self, n: u64) -> u128 {
+        let (p, pe) = (self.p, self.pe);
+        let period = if p == 2 && self.e >= 3 { 1 } else { pe - 1 };
+        let mut n = n as u128;
+        let mut res = 1 % pe;
+        while n > 0 {
+            if (n / pe) % 2 == 1 {
+                res = mul_mod_wide(res, period, pe);
+            }
+            res = mul_mod_wide(res, self.up_to(n % pe), pe);
+            n /= p;
+        }
+
+        res
+    }
+}
+
+/// a b mod m for a, b < m <= 2^64
+fn mul_mod_wide(a: u128, b: u128, m: u128) -> u128 {
+    a * b % m
+}
+
+fn pow_mod_wide(mut a: u128, mut b: u128, m: u128) -> u128 {
+    let mut res = 1 % m;
+    while b > 0 {
+        if b & 1 == 1 {
+            res = mul_mod_wide(res, a, m);
+        }
+        a = mul_mod_wide(a, a, m);
+        b >>= 1;
+    }
+
+    res
+}
+
+/// 1 with room for len coefficients
+fn polynomial_one(len: usize, m: u128) -> Vec<u128> {
+    let mut one = vec![0; len];
+    one[0] = 1 % m;
+
+    one
+}
+
+/// a b mod m, keeping the degrees below a.len()
+fn polynomial_mul(a: &[u128], b: &[u128], m: u128) -> Vec<u128> {
+    let mut product = vec![0; a.len()];
+    for (i, &ai) in a.iter().enumerate() {
+        for (j, &bj) in b.iter().enumerate().take(a.len() - i) {
+            product[i + j] = (product[i + j] + mul_mod_wide(ai, bj, m)) % m;
+        }
+    }
+
+    product
+}
+
+/// a(y + s) mod m, from a_d (y + s)^d = Σ a_d C(d, i) s^(d - i) y^i
+fn polynomial_shift(a: &[u128], s: u128, binom: &[Vec<u128>], m: u128) -> Vec<u128> {
+    let mut powers = vec![1 % m; a.len()];
+    for d in 1..a.len() {
+        powers[d] = mul_mod_wide(powers[d - 1], s, m);
+    }
+    let mut shifted = vec![0; a.len()];
+    for (d, &ad) in a.iter().enumerate() {
+        for i in 0..=d {
+            let term = mul_mod_wide(ad, mul_mod_wide(binom[d][i], powers[d - i], m), m);
+            shifted[i] = (shifted[i] + term) % m;
+        }
+    }
+
+    shifted
+}
+
+/// a(y) mod m by Horner's rule
+fn polynomial_eval(a: &[u128], y: u128, m: u128) -> u128 {
+    a.iter().rev().fold(0, |acc, &c| (mul_mod_wide(acc, y, m) + c) % m)
+}
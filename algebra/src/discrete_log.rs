@@ -0,0 +1,227 @@
+//! [Discrete Logarithm](https://cp-algorithms.com/algebra/discrete-log.html)
+//!
+//! Every function here returns the least x >= 0 with a^x = b (mod m), or None when there is none.
+use std::collections::HashMap;
+
+use rand::{thread_rng, Rng};
+
+use crate::binary_exponentiation::{binary_exponentiation_mod, mul_mod};
+use crate::factorizer::{AutoFactorizer, Factorizer};
+use crate::gcd::gcd;
+use crate::linear_congruence::{solve_congruence_system, solve_linear_congruence};
+use crate::roots::isqrt;
+use crate::totient::multiplicative_order;
+
+/// Baby-step giant-step for any modulus
+///
+/// Common factors of a and m are divided out first, which leaves k a^x = b (mod m') with a
+/// coprime to m'. That is then solved by meeting in the middle in O(sqrt m) time and memory.
+/// ```
+/// use algebra::discrete_log::discrete_log;
+///
+/// assert_eq!(discrete_log(2, 3, 5), Some(3));
+/// assert_eq!(discrete_log(3, 1, 7), Some(0));
+/// assert_eq!(discrete_log(2, 3, 7), None);
+/// assert_eq!(discrete_log(5, 33, 58), Some(9));
+///
+/// // Not coprime to m
+/// assert_eq!(discrete_log(2, 0, 8), Some(3));
+/// assert_eq!(discrete_log(6, 36, 1296), Some(2));
+/// assert_eq!(discrete_log(2, 3, 8), None);
+///
+/// let p = 1000000007;
+/// assert_eq!(discrete_log(5, 123456789, p), Some(981640996));
+/// ```
+pub fn discrete_log(a: u64, b: u64, m: u64) -> Option<u64> {
+    let mut m = m;
+    let mut a = a % m;
+    let mut b = b % m;
+    // k a^x = b with k = a^add / g1 g2 ... after dividing out the gcds
+    let mut k = 1 % m;
+    let mut add = 0;
+    loop {
+        let g = gcd(a, m);
+        if g == 1 {
+            break;
+        }
+        if b == k {
+            return Some(add);
+        }
+        if !b.is_multiple_of(g) {
+            return None;
+        }
+        b /= g;
+        m /= g;
+        add += 1;
+        k = mul_mod(k, a / g, m);
+        a %= m;
+    }
+
+    let n = isqrt(m) + 1;
+    let an = binary_exponentiation_mod(a, n, m);
+    // b a^q for q in [0, n], keeping the largest q so the least x is found
+    let mut baby_steps = HashMap::new();
+    let mut cur = b;
+    for q in 0..=n {
+        baby_steps.insert(cur, q);
+        cur = mul_mod(cur, a, m);
+    }
+    // k a^(np) for p in [1, n]
+    let mut cur = k;
+    for p in 1..=n {
+        cur = mul_mod(cur, an, m);
+        if let Some(&q) = baby_steps.get(&cur) {
+            return Some(n * p - q + add);
+        }
+    }
+
+    None
+}
+
+/// Least x in [0, order) with g^x = h (mod m), for g coprime to m of the given order
+fn baby_step_giant_step(g: u64, h: u64, m: u64, order: u64) -> Option<u64> {
+    let n = isqrt(order) + 1;
+    let mut baby_steps = HashMap::new();
+    let mut gj = 1 % m;
+    for j in 0..n {
+        baby_steps.entry(gj).or_insert(j);
+        gj = mul_mod(gj, g, m);
+    }
+    // g^-n
+    let giant = binary_exponentiation_mod(g, order - n % order, m);
+    let mut gamma = h % m;
+    for i in 0..n {
+        if let Some(&j) = baby_steps.get(&gamma) {
+            return Some((i * n + j) % order);
+        }
+        gamma = mul_mod(gamma, giant, m);
+    }
+
+    None
+}
+
+/// Pohlig–Hellman for a coprime to m
+///
+/// The logarithm is found modulo each prime power p^e dividing the order of a, one base p digit
+/// at a time with baby-step giant-step in the subgroup of order p, and the pieces are joined by
+/// the Chinese remainder theorem. Takes O(Σ e (log n + sqrt p)), fast whenever the order is smooth.
+/// None is also returned when a isn't coprime to m.
+/// ```
+/// use algebra::discrete_log::pohlig_hellman;
+///
+/// assert_eq!(pohlig_hellman(2, 3, 5), Some(3));
+/// assert_eq!(pohlig_hellman(2, 3, 7), None);
+/// assert_eq!(pohlig_hellman(2, 4, 6), None);
+///
+/// // p - 1 = 2^23 * 7 * 17
+/// let p = 998244353;
+/// assert_eq!(pohlig_hellman(3, 123456789, p), Some(772453214));
+///
+/// // p - 1 = 2^2 * 3^2 * 5 * 7 * 11 * 13 * 17 * 19 * 23 * 29 * 31 * 37 * 41 * 43
+/// let p = 78496567990020181;
+/// let x = 12345678901234567;
+/// let b = algebra::binary_exponentiation::binary_exponentiation_mod(3, x, p);
+/// let y = pohlig_hellman(3, b, p).unwrap();
+/// assert_eq!(algebra::binary_exponentiation::binary_exponentiation_mod(3, y, p), b);
+/// ```
+pub fn pohlig_hellman(a: u64, b: u64, m: u64) -> Option<u64> {
+    let order = multiplicative_order(a, m)?;
+    let b = b % m;
+
+    // x = r (mod modulus) for the prime powers handled so far
+    let mut r: u64 = 0;
+    let mut modulus: u64 = 1;
+    for &(p, e) in AutoFactorizer::new().factor(order).factors() {
+        let pe = p.pow(e);
+        // Project onto the subgroup of order p^e
+        let g = binary_exponentiation_mod(a, order / pe, m);
+        let h = binary_exponentiation_mod(b, order / pe, m);
+        let g_inv = binary_exponentiation_mod(g, pe - 1, m);
+        // Generator of the subgroup of order p
+        let gamma = binary_exponentiation_mod(g, pe / p, m);
+        let mut x: u64 = 0;
+        let mut pk = 1;
+        for k in 0..e {
+            // (g^-x h)^(p^(e - 1 - k)) = gamma^d
+            let hk = mul_mod(binary_exponentiation_mod(g_inv, x, m), h, m);
+            let hk = binary_exponentiation_mod(hk, pe / pk / p, m);
+            let d = baby_step_giant_step(gamma, hk, m, p)?;
+            x += d * pk;
+            if k + 1 < e {
+                pk *= p;
+            }
+        }
+        (r, modulus) = solve_congruence_system(&[(1, r, modulus), (1, x, pe)]).unwrap();
+    }
+
+    if binary_exponentiation_mod(a, r, m) == b {
+        Some(r)
+    } else {
+        None
+    }
+}
+
+/// Pollard's rho for logarithms, for a coprime to m
+///
+/// A pseudo-random walk over a^α b^β runs until Floyd's cycle detection finds two equal
+/// elements, giving a linear congruence for x. Expected O(sqrt n) steps for a of order n,
+/// like baby-step giant-step, but in constant memory.
+/// None is also returned when a isn't coprime to m.
+/// ```
+/// use algebra::discrete_log::discrete_log_rho;
+///
+/// assert_eq!(discrete_log_rho(2, 3, 5), Some(3));
+/// assert_eq!(discrete_log_rho(2, 3, 7), None);
+///
+/// let p = 1000000007;
+/// assert_eq!(discrete_log_rho(5, 123456789, p), Some(981640996));
+///
+/// // 2 has prime order 1019 mod 2039
+/// assert_eq!(discrete_log_rho(4, 1024, 2039), Some(5));
+/// ```
+pub fn discrete_log_rho(a: u64, b: u64, m: u64) -> Option<u64> {
+    let n = multiplicative_order(a, m)?;
+    let b = b % m;
+    if n < 64 {
+        return (0..n).find(|&x| binary_exponentiation_mod(a, x, m) == b);
+    }
+    if gcd(b, m) != 1 {
+        return None;
+    }
+
+    // Candidates are checked one by one, so a walk is only used when their number is small
+    const MAX_CANDIDATES: u64 = 1 << 10;
+    let add = |x: u64, y: u64| ((x as u128 + y as u128) % n as u128) as u64;
+    let step = |(x, alpha, beta): (u64, u64, u64)| match x % 3 {
+        0 => (mul_mod(x, b, m), alpha, add(beta, 1)),
+        1 => (mul_mod(x, x, m), add(alpha, alpha), add(beta, beta)),
+        _ => (mul_mod(x, a, m), add(alpha, 1), beta),
+    };
+    let mut rng = thread_rng();
+    loop {
+        let alpha = rng.gen_range(0, n);
+        let beta = rng.gen_range(0, n);
+        let x = mul_mod(binary_exponentiation_mod(a, alpha, m), binary_exponentiation_mod(b, beta, m), m);
+        let mut tortoise = step((x, alpha, beta));
+        let mut hare = step(step((x, alpha, beta)));
+        while tortoise.0 != hare.0 {
+            tortoise = step(tortoise);
+            hare = step(step(hare));
+        }
+
+        // a^α1 b^β1 = a^α2 b^β2, so (β1 - β2) x = α2 - α1 (mod n)
+        let (_, alpha1, beta1) = tortoise;
+        let (_, alpha2, beta2) = hare;
+        let r = add(beta1, n - beta2);
+        let s = add(alpha2, n - alpha1);
+        let d = gcd(r, n);
+        if r == 0 || d > MAX_CANDIDATES {
+            continue;
+        }
+        // The true logarithm would satisfy the congruence
+        let (x0, nd) = solve_linear_congruence(r, s, n)?;
+        return (0..d)
+            .map(|i| x0 + i * nd)
+            .find(|&x| binary_exponentiation_mod(a, x, m) == b);
+    }
+}
@@ -0,0 +1,160 @@
+//! Divisors of 64-bit integers
+//!
+//! Everything is computed from the prime factorization, so the cost is that of
+//! [`AutoFactorizer`] plus the number of divisors, never a scan up to sqrt(n).
+use crate::factorizer::{AutoFactorizer, Factorization, Factorizer};
+
+/// Divisors of n in increasing order, empty for n = 0
+/// ```
+/// use algebra::divisors::divisors;
+///
+/// assert_eq!(divisors(1), vec![1]);
+/// assert_eq!(divisors(12), vec![1, 2, 3, 4, 6, 12]);
+/// assert_eq!(divisors(97), vec![1, 97]);
+///
+/// // Highly composite, 184320 divisors
+/// let n = 18401055938125660800;
+/// let d = divisors(n);
+/// assert_eq!(d.len(), 184320);
+/// assert!(d.windows(2).all(|w| w[0] < w[1] && n % w[1] == 0));
+/// ```
+pub fn divisors(n: u64) -> Vec<u64> {
+    let mut divisors: Vec<u64> = divisors_unsorted(n).collect();
+    divisors.sort_unstable();
+
+    divisors
+}
+
+/// Divisors of n in no particular order, empty for n = 0
+/// ```
+/// use algebra::divisors::divisors_unsorted;
+///
+/// let mut d: Vec<u64> = divisors_unsorted(60).collect();
+/// assert_eq!(d.len(), 12);
+/// d.sort();
+/// assert_eq!(d, vec![1, 2, 3, 4, 5, 6, 10, 12, 15, 20, 30, 60]);
+/// assert_eq!(divisors_unsorted(0).count(), 0);
+/// ```
+pub fn divisors_unsorted(n: u64) -> Divisors {
+    Divisors::new(&factorization(n), n != 0)
+}
+
+/// Divisors of n other than n itself, in increasing order
+/// ```
+/// use algebra::divisors::proper_divisors;
+///
+/// assert_eq!(proper_divisors(28), vec![1, 2, 4, 7, 14]);
+/// assert_eq!(proper_divisors(1), vec![]);
+/// ```
+pub fn proper_divisors(n: u64) -> Vec<u64> {
+    let mut divisors = divisors(n);
+    divisors.pop();
+
+    divisors
+}
+
+/// Number of divisors d(n), 0 for n = 0
+/// ```
+/// use algebra::divisors::num_divisors;
+///
+/// assert_eq!(num_divisors(12), 6);
+/// assert_eq!(num_divisors(1), 1);
+/// assert_eq!(num_divisors(18401055938125660800), 184320);
+/// ```
+pub fn num_divisors(n: u64) -> u64 {
+    if n == 0 {
+        return 0;
+    }
+
+    factorization(n).factors().iter().map(|&(_, e)| e as u64 + 1).product()
+}
+
+/// Sum of divisors σ(n), 0 for n = 0
+///
+/// σ(n) can exceed 2^64 for large n, hence the u128.
+/// ```
+/// use algebra::divisors::sum_divisors;
+///
+/// assert_eq!(sum_divisors(12), 28);
+/// assert_eq!(sum_divisors(1), 1);
+/// assert_eq!(sum_divisors(18446744073709551615), 31421980989189888768);
+/// ```
+pub fn sum_divisors(n: u64) -> u128 {
+    if n == 0 {
+        return 0;
+    }
+
+    factorization(n)
+        .factors()
+        .iter()
+        .map(|&(p, e)| {
+            let p = p as u128;
+            // 1 + p + ... + p^e, summed directly as p^(e + 1) can overflow
+            let mut sum = 1;
+            let mut pk = 1;
+            for _ in 0..e {
+                pk *= p;
+                sum += pk;
+            }
+            sum
+        })
+        .product()
+}
+
+fn factorization(n: u64) -> Factorization {
+    AutoFactorizer::new().factor(n)
+}
+
+/// Iterator over the divisors of a number given its factorization
+///
+/// Exponents are stepped like the digits of a mixed radix counter, so every divisor
+/// costs a multiplication or a division or two.
+pub struct Divisors {
+    factors: Vec<(u64, u32)>,
+    exponents: Vec<u32>,
+    // p^e for the current exponent of each prime
+    powers: Vec<u64>,
+    current: u64,
+    done: bool,
+}
+
+impl Divisors {
+    fn new(factorization: &Factorization, nonempty: bool) -> Divisors {
+        let factors = factorization.factors().to_vec();
+        Divisors {
+            exponents: vec![0; factors.len()],
+            powers: vec![1; factors.len()],
+            factors,
+            current: 1,
+            done: !nonempty,
+        }
+    }
+}
+
+impl Iterator for Divisors {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        if self.done {
+            return None;
+        }
+        let divisor = self.current;
+
+        self.done = true;
+        for i in 0..self.factors.len() {
+            let (p, e) = self.factors[i];
+            if self.exponents[i] < e {
+                self.exponents[i] += 1;
+                self.powers[i] *= p;
+                self.current *= p;
+                self.done = false;
+                break;
+            }
+            self.current /= self.powers[i];
+            self.exponents[i] = 0;
+            self.powers[i] = 1;
+        }
+
+        Some(divisor)
+    }
+}
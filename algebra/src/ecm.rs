@@ -0,0 +1,287 @@
+//! [Lenstra Elliptic Curve Factorization](https://en.wikipedia.org/wiki/Lenstra_elliptic-curve_factorization)
+//!
+//! Works on Montgomery curves By^2 = x^3 + Ax^2 + x with Suyama's parametrization.
+//! Points are kept as projective (X : Z) and only x-coordinates are tracked,
+//! so no modular inversions are needed until the final gcd.
+use num::{BigInt, Integer};
+use rand::{thread_rng, Rng};
+
+use crate::factorization::prime_power_below;
+use crate::prime::BitSieve;
+use crate::ring::{BigRing, MontgomeryRing, Ring};
+
+/// Bounds for ECM
+pub struct EcmParams {
+    /// Stage 1 bound, every prime power up to b1 multiplies the starting point
+    pub b1: u64,
+    /// Stage 2 bound, allows the group order to have one more prime in (b1, b2]
+    pub b2: u64,
+    /// Number of random curves tried before giving up
+    pub curves: usize,
+}
+
+impl EcmParams {
+    pub fn new(b1: u64, b2: u64, curves: usize) -> EcmParams {
+        EcmParams { b1, b2, curves }
+    }
+
+    /// Bounds expected to find a factor with up to `digits` decimal digits
+    ///
+    /// Stage 1 bounds and curve counts follow the GMP-ECM recommendations, with b2 = 100 * b1
+    /// ```
+    /// let params = algebra::ecm::EcmParams::for_digits(20);
+    /// assert_eq!((params.b1, params.b2, params.curves), (11000, 1100000, 90));
+    /// ```
+    pub fn for_digits(digits: u32) -> EcmParams {
+        let (b1, curves) = match digits {
+            0..=15 => (2000, 25),
+            16..=20 => (11000, 90),
+            21..=25 => (50000, 300),
+            26..=30 => (250000, 700),
+            31..=35 => (1000000, 1800),
+            36..=40 => (3000000, 5100),
+            41..=45 => (11000000, 10600),
+            _ => (43000000, 19300),
+        };
+        EcmParams::new(b1, 100 * b1, curves)
+    }
+}
+
+impl Default for EcmParams {
+    fn default() -> EcmParams {
+        EcmParams::for_digits(20)
+    }
+}
+
+/// Lenstra's ECM over big integers
+///
+/// Returns a non-trivial factor of n, or None if no curve found one.
+/// n should be composite; prime n never yields a factor.
+/// ```
+/// use num::BigInt;
+/// use algebra::ecm::{ecm, EcmParams};
+///
+/// let p = BigInt::from(1000000007_u64);
+/// let q: BigInt = "170141183460469231731687303715884105727".parse().unwrap();
+/// let n = &p * &q;
+/// let d = ecm(&n, &EcmParams::for_digits(15)).unwrap();
+/// assert!(d == p || d == q);
+/// ```
+pub fn ecm(n: &BigInt, params: &EcmParams) -> Option<BigInt> {
+    if n.is_even() {
+        return Some(BigInt::from(2));
+    }
+    ecm_with(&BigRing::new(n), params)
+}
+
+/// Lenstra's ECM for 128 bit integers using Montgomery multiplication
+///
+/// Same as [`ecm`] without big integer allocations.
+/// ```
+/// use algebra::ecm::{ecm_u128, EcmParams};
+///
+/// let p = 1000000000039_u128;
+/// let q = 99999999999973_u128;
+/// let d = ecm_u128(p * q, &EcmParams::for_digits(15)).unwrap();
+/// assert!(d == p || d == q);
+///
+/// // Most curves have smooth orders mod both factors, which stage 1 has to back up from
+/// let d = ecm_u128(5839 * 12197, &EcmParams::for_digits(15)).unwrap();
+/// assert!(d == 5839 || d == 12197);
+/// ```
+pub fn ecm_u128(n: u128, params: &EcmParams) -> Option<u128> {
+    if n.is_multiple_of(2) {
+        return Some(2);
+    }
+    ecm_with(&MontgomeryRing::new(n), params)
+}
+
+/// Projective x-coordinate (X : Z)
+#[derive(Clone)]
+struct Point<E> {
+    x: E,
+    z: E,
+}
+
+/// Montgomery curve given by (A + 2) / 4 = a24 / c24
+struct Curve<'a, R: Ring> {
+    ring: &'a R,
+    a24: R::Elem,
+    c24: R::Elem,
+}
+
+impl<'a, R: Ring> Curve<'a, R> {
+    /// Suyama's parametrization, the group order is divisible by 12
+    fn suyama(ring: &'a R, sigma: u64) -> (Curve<'a, R>, Point<R::Elem>) {
+        let sigma = ring.elem(sigma);
+        let u = ring.sub(&ring.mul(&sigma, &sigma), &ring.elem(5));
+        let v = ring.mul(&ring.elem(4), &sigma);
+        let u3 = ring.mul(&ring.mul(&u, &u), &u);
+        let v3 = ring.mul(&ring.mul(&v, &v), &v);
+        let vu = ring.sub(&v, &u);
+        let vu3 = ring.mul(&ring.mul(&vu, &vu), &vu);
+        let a24 = ring.mul(&vu3, &ring.add(&ring.mul(&ring.elem(3), &u), &v));
+        let c24 = ring.mul(&ring.mul(&ring.elem(16), &u3), &v);
+
+        (Curve { ring, a24, c24 }, Point { x: u3, z: v3 })
+    }
+
+    fn double(&self, p: &Point<R::Elem>) -> Point<R::Elem> {
+        let r = self.ring;
+        let s = r.add(&p.x, &p.z);
+        let d = r.sub(&p.x, &p.z);
+        let s2 = r.mul(&s, &s);
+        let d2 = r.mul(&d, &d);
+        let t = r.sub(&s2, &d2);
+        let c24_d2 = r.mul(&self.c24, &d2);
+
+        Point {
+            x: r.mul(&c24_d2, &s2),
+            z: r.mul(&t, &r.add(&c24_d2, &r.mul(&self.a24, &t))),
+        }
+    }
+
+    /// P + Q given P - Q
+    fn add(&self, p: &Point<R::Elem>, q: &Point<R::Elem>, diff: &Point<R::Elem>) -> Point<R::Elem> {
+        let r = self.ring;
+        let u = r.mul(&r.sub(&p.x, &p.z), &r.add(&q.x, &q.z));
+        let v = r.mul(&r.add(&p.x, &p.z), &r.sub(&q.x, &q.z));
+        let sum = r.add(&u, &v);
+        let dif = r.sub(&u, &v);
+
+        Point {
+            x: r.mul(&diff.z, &r.mul(&sum, &sum)),
+            z: r.mul(&diff.x, &r.mul(&dif, &dif)),
+        }
+    }
+
+    /// k * P for k >= 1 with the Montgomery ladder
+    fn multiply(&self, k: u64, p: &Point<R::Elem>) -> Point<R::Elem> {
+        let mut r0 = p.clone();
+        let mut r1 = self.double(p);
+        for bit in (0..63 - k.leading_zeros()).rev() {
+            if (k >> bit) & 1 == 1 {
+                r0 = self.add(&r1, &r0, p);
+                r1 = self.double(&r1);
+            } else {
+                r1 = self.add(&r0, &r1, p);
+                r0 = self.double(&r0);
+            }
+        }
+
+        r0
+    }
+}
+
+/// Giant step size of stage 2
+const D: u64 = 2310;
+
+fn ecm_with<R: Ring>(ring: &R, params: &EcmParams) -> Option<R::Int> {
+    let sieve = BitSieve::new(params.b1.max(params.b2) as usize);
+    let mut rng = thread_rng();
+    for _ in 0..params.curves {
+        let sigma = rng.gen_range(6, 1 << 32);
+        if let Some(d) = ecm_curve(ring, sigma, params, &sieve) {
+            return Some(d);
+        }
+    }
+
+    None
+}
+
+fn ecm_curve<R: Ring>(ring: &R, sigma: u64, params: &EcmParams, sieve: &BitSieve) -> Option<R::Int> {
+    let (curve, start) = Curve::suyama(ring, sigma);
+    if let Some(d) = ring.factor(&curve.c24) {
+        return Some(d);
+    }
+
+    // Stage 1, Q = kQ for k the product of all prime powers up to b1
+    let stage1_primes = || sieve.iter().map(|p| p as u64).take_while(|&p| p <= params.b1);
+    let mut q = start.clone();
+    for p in stage1_primes() {
+        q = curve.multiply(prime_power_below(p, params.b1), &q);
+    }
+    if ring.is_zero(&q.z) {
+        // kQ = O mod every factor, so go again one prime at a time to catch a factor alone
+        q = start;
+        for p in stage1_primes() {
+            let mut pk = 1;
+            while pk <= params.b1 / p {
+                q = curve.multiply(p, &q);
+                if let Some(d) = ring.factor(&q.z) {
+                    return Some(d);
+                }
+                if ring.is_zero(&q.z) {
+                    return None;
+                }
+                pk *= p;
+            }
+        }
+    }
+    if let Some(d) = ring.factor(&q.z) {
+        return Some(d);
+    }
+    if params.b2 <= params.b1 {
+        return None;
+    }
+
+    // Stage 2, a prime s = mD ± d in (b1, b2] has sQ = O mod p exactly when
+    // x(mDQ) = x(dQ) mod p, so accumulate X_mD * Z_d - X_d * Z_mD over all such s
+    let q2 = curve.double(&q);
+    let mut baby = vec![q.clone(), curve.add(&q2, &q, &q)];
+    while baby.len() <= (D / 4) as usize {
+        let next = curve.add(&baby[baby.len() - 1], &q2, &baby[baby.len() - 2]);
+        baby.push(next);
+    }
+    let dq = curve.multiply(D, &q);
+    let mut acc = ring.elem(1);
+    // Terms since the last gcd, replayed one by one if their product hits every factor at once
+    let mut batch = Vec::with_capacity(STAGE2_BATCH);
+    let mut m = (params.b1 + 1 + D / 2) / D;
+    let mut giant = if m == 0 {
+        Point { x: ring.elem(1), z: ring.elem(0) }
+    } else {
+        curve.multiply(m * D, &q)
+    };
+    let mut prev = if m >= 2 { curve.multiply((m - 1) * D, &q) } else { giant.clone() };
+    let mut primes = sieve.iter().map(|p| p as u64).skip_while(|&p| p <= params.b1).peekable();
+    while let Some(s) = primes.next() {
+        while s > m * D + D / 2 {
+            let next = match m {
+                0 => dq.clone(),
+                1 => curve.double(&dq),
+                _ => curve.add(&giant, &dq, &prev),
+            };
+            prev = std::mem::replace(&mut giant, next);
+            m += 1;
+        }
+        let d = s.abs_diff(m * D);
+        let b = &baby[(d / 2) as usize];
+        batch.push(ring.sub(&ring.mul(&giant.x, &b.z), &ring.mul(&b.x, &giant.z)));
+        if batch.len() < STAGE2_BATCH && primes.peek().is_some() {
+            continue;
+        }
+
+        let product = batch.iter().fold(acc.clone(), |acc, t| ring.mul(&acc, t));
+        if ring.is_zero(&product) {
+            for t in batch.iter() {
+                acc = ring.mul(&acc, t);
+                if let Some(d) = ring.factor(&acc) {
+                    return Some(d);
+                }
+            }
+            return None;
+        }
+        if let Some(d) = ring.factor(&product) {
+            return Some(d);
+        }
+        acc = product;
+        batch.clear();
+    }
+
+    None
+}
+
+/// Stage 2 terms multiplied together between gcds
+const STAGE2_BATCH: usize = 1024;
+
@@ -1,4 +1,17 @@
 //! [Integer Factorization](https://cp-algorithms.com/algebra/factorization.html)
+use num::{BigInt, Integer, One, Signed, ToPrimitive, Zero};
+use std::cmp;
+
+use crate::binary_exponentiation::mul_mod;
+use crate::ecm::{ecm, ecm_u128, EcmParams};
+use crate::gcd::gcd;
+use crate::primality::{deterministic_millerrabin, millerrabin_bigint};
+use crate::prime::BitSieve;
+use crate::qs::siqs;
+use crate::ring::{self, BigRing, MontgomeryRing, Ring};
+use crate::roots::{
+    icbrt, is_perfect_square, is_perfect_square_u128, isqrt, isqrt_u128, perfect_power, perfect_power_bigint,
+};
 
 /// Trial Division
 /// ```
@@ -9,7 +22,7 @@ pub fn trial_division(mut n: u64) -> Vec<u64> {
     let mut factorization: Vec<u64> = Vec::new();
 
     for d in 2..n {
-        if d * d > n {
+        if d > n / d {
             break;
         }
 
@@ -30,34 +43,44 @@ pub fn trial_division(mut n: u64) -> Vec<u64> {
 /// ```
 /// assert_eq!(algebra::factorization::trial_division_wheel(60), vec![2, 2, 3, 5]);
 /// assert_eq!(algebra::factorization::trial_division_wheel(210), vec![2, 3, 5, 7]);
+/// assert_eq!(algebra::factorization::trial_division_wheel(49), vec![7, 7]);
 /// ```
 pub fn trial_division_wheel(mut n: u64) -> Vec<u64> {
-    let mut factorization: Vec<u64> = Vec::new();
+    let mut factorization = wheel_division(&mut n, u64::MAX);
+
+    if n > 1 {
+        factorization.push(n);
+    }
+
+    factorization
+}
+
+/// Divides n by 2, 3, 5 and then by the numbers below `bound` coprime to 30, up to sqrt(n)
+///
+/// Returns the prime factors found in increasing order and leaves the cofactor in n
+pub(crate) fn wheel_division(n: &mut u64, bound: u64) -> Vec<u64> {
+    let mut factorization = Vec::new();
+    if *n == 0 {
+        return factorization;
+    }
 
     for &d in [2, 3, 5].iter() {
-        while n % d == 0 {
+        while n.is_multiple_of(d) {
             factorization.push(d);
-            n /= d;
+            *n /= d;
         }
     }
 
     let mut i = 0;
     let mut d = 7;
     static INCREMENTS: [u64; 8] = [4, 2, 4, 2, 4, 6, 2, 6];
-    while d * d <= n {
-        d += INCREMENTS[i];
-        i += 1;
-        while n % d == 0 {
+    while d < bound && d <= *n / d {
+        while n.is_multiple_of(d) {
             factorization.push(d);
-            n /= d;
-        }
-        if i == 8 {
-            i = 0;
+            *n /= d;
         }
-    }
-
-    if n > 1 {
-        factorization.push(n);
+        d += INCREMENTS[i];
+        i = (i + 1) % 8;
     }
 
     factorization
@@ -72,7 +95,7 @@ pub fn trial_division_primes_precomputed(mut n: u64, primes: &[u64]) -> Vec<u64>
     let mut factorization = Vec::new();
 
     for &d in primes {
-        if d * d > n {
+        if d > n / d {
             break;
         }
 
@@ -89,20 +112,742 @@ pub fn trial_division_primes_precomputed(mut n: u64, primes: &[u64]) -> Vec<u64>
 }
 
 /// Fermat's factorization method
+///
+/// Writes n = a^2 - b^2 = (a - b)(a + b) searching a upwards from sqrt(n),
+/// which is fast when n has two factors close to sqrt(n).
+/// Returns the factors (p, q) with p <= q, or None for even, prime and n < 3.
+/// ```
+/// assert_eq!(algebra::factorization::fermat_factorization(49), Some((7, 7)));
+/// assert_eq!(algebra::factorization::fermat_factorization(56), None);
+/// assert_eq!(algebra::factorization::fermat_factorization(1000000007), None);
+/// assert_eq!(algebra::factorization::fermat_factorization(4294967291 * 4294967279), Some((4294967279, 4294967291)));
+/// ```
+pub fn fermat_factorization(n: u64) -> Option<(u64, u64)> {
+    if n < 3 || n.is_multiple_of(2) || deterministic_millerrabin(n) {
+        return None;
+    }
+
+    let n = n as u128;
+    let mut a = isqrt(n as u64) as u128;
+    if a * a < n {
+        a += 1;
+    }
+    let mut b2 = a * a - n;
+    while !is_perfect_square_u128(b2) {
+        b2 += 2 * a + 1;
+        a += 1;
+    }
+    let b = isqrt_u128(b2);
+
+    Some(((a - b) as u64, (a + b) as u64))
+}
+
+/// Fermat's factorization method for 128 bit integers
+///
+/// Same as [`fermat_factorization`]
+/// ```
+/// let p = 18446744073709551557_u128;
+/// let q = 18446744073709551533_u128;
+/// assert_eq!(algebra::factorization::fermat_factorization_u128(p * q), Some((q, p)));
+/// ```
+pub fn fermat_factorization_u128(n: u128) -> Option<(u128, u128)> {
+    if n < 3 || n.is_multiple_of(2) || millerrabin_bigint(&BigInt::from(n)) {
+        return None;
+    }
+
+    let mut a = isqrt_u128(n);
+    if a * a < n {
+        a += 1;
+    }
+    // a^2 - n stays below 2a, so it can't overflow before a^2 would
+    let mut b2 = a.checked_mul(a)? - n;
+    while !is_perfect_square_u128(b2) {
+        b2 = b2.checked_add(2 * a + 1)?;
+        a += 1;
+    }
+    let b = isqrt_u128(b2);
+
+    Some((a - b, a + b))
+}
+
+/// Lehman's method
+///
+/// Fermat's method on 4kn for k up to n^(1/3), after trial division up to n^(1/3).
+/// Runs in O(n^(1/3)) however far apart the factors are.
+/// Returns the factors (p, q) with p <= q, or None for even, prime and n < 3.
+/// ```
+/// assert_eq!(algebra::factorization::lehman_factorization(3 * 1000000007), Some((3, 1000000007)));
+/// assert_eq!(algebra::factorization::lehman_factorization(1000003 * 1000000007), Some((1000003, 1000000007)));
+/// assert_eq!(algebra::factorization::lehman_factorization(97), None);
+/// ```
+pub fn lehman_factorization(n: u64) -> Option<(u64, u64)> {
+    if n < 3 || n.is_multiple_of(2) || deterministic_millerrabin(n) {
+        return None;
+    }
+
+    let cbrt = match trial_division_cbrt(n) {
+        Ok(cbrt) => cbrt,
+        Err(factors) => return Some(factors),
+    };
+
+    // a <= sqrt(4kn) + n^(1/6) / (4 sqrt(k)) means a^2 - 4kn <= n^(2/3) + n^(1/3) / 16k,
+    // which is bounded by an integer slightly above it
+    let n128 = n as u128;
+    let cbrt = cbrt as u128;
+    let b2_max = (cbrt + 1) * (cbrt + 1) + cbrt + 1;
+    for k in 1..=cbrt {
+        let four_kn = 4 * k * n128;
+        let mut a = isqrt_u128(four_kn);
+        if a * a < four_kn {
+            a += 1;
+        }
+        while a * a - four_kn <= b2_max {
+            let b2 = a * a - four_kn;
+            if is_perfect_square(b2 as u64) {
+                let d = gcd((a + isqrt(b2 as u64) as u128) as u64 % n, n);
+                if d > 1 && d < n {
+                    return Some((cmp::min(d, n / d), cmp::max(d, n / d)));
+                }
+            }
+            a += 1;
+        }
+    }
+
+    None
+}
+
+/// Hart's one line factoring algorithm
+///
+/// After trial division up to n^(1/3), tries s = ceil(sqrt(in)) for i = 1, 2, ...
+/// until s^2 mod n is a square t^2, then gcd(s - t, n) is a factor.
+/// Heuristically O(n^(1/3)), and very fast for n with factors whose ratio
+/// is close to a ratio of small numbers.
+/// The search gives up after i = 64 n^(1/3), a few times more than it takes in practice.
+/// Returns the factors (p, q) with p <= q, or None for even, prime, n < 3 and n it gives up on.
+/// ```
+/// assert_eq!(algebra::factorization::hart_one_line_factorization(1000003 * 1000000007), Some((1000003, 1000000007)));
+/// assert_eq!(algebra::factorization::hart_one_line_factorization(101 * 101), Some((101, 101)));
+/// assert_eq!(algebra::factorization::hart_one_line_factorization(97), None);
+/// assert_eq!(algebra::factorization::hart_one_line_factorization(2097229 * 8795770072637), Some((2097229, 8795770072637)));
+/// ```
+pub fn hart_one_line_factorization(n: u64) -> Option<(u64, u64)> {
+    if n < 3 || n.is_multiple_of(2) || deterministic_millerrabin(n) {
+        return None;
+    }
+
+    let cbrt = match trial_division_cbrt(n) {
+        Ok(cbrt) => cbrt,
+        Err(factors) => return Some(factors),
+    };
+
+    let n128 = n as u128;
+    for i in 1..=64 * cbrt as u128 {
+        let mut s = isqrt_u128(n128 * i);
+        if s * s < n128 * i {
+            s += 1;
+        }
+        let m = s * s % n128;
+        if is_perfect_square(m as u64) {
+            let d = gcd(((s - isqrt(m as u64) as u128) % n128) as u64, n);
+            if d > 1 && d < n {
+                return Some((cmp::min(d, n / d), cmp::max(d, n / d)));
+            }
+        }
+    }
+
+    None
+}
+
+/// Trial division of an odd n by odd numbers up to n^(1/3)
+///
+/// Returns the factors (d, n / d) for the first divisor d found, or n^(1/3) if there's none
+fn trial_division_cbrt(n: u64) -> Result<u64, (u64, u64)> {
+    let cbrt = icbrt(n);
+    for d in (3..=cbrt).step_by(2) {
+        if n.is_multiple_of(d) {
+            return Err((d, n / d));
+        }
+    }
+
+    Ok(cbrt)
+}
+
+/// Shanks' square forms factorization
+///
+/// Walks the continued fraction of sqrt(kn) until a square form shows up, then its
+/// reduced square root cycle ends on a factor. Runs in O(n^(1/4)) without randomness.
+/// The multipliers k are raced against each other, since one of them usually finishes much sooner.
+/// Returns a non-trivial factor of n, or None for primes, n < 4 and the rare n no multiplier splits.
+/// ```
+/// let d = algebra::factorization::squfof(10007 * 1000003);
+/// assert!(d == Some(10007) || d == Some(1000003));
+/// let d = algebra::factorization::squfof(2147483647 * 2147483629);
+/// assert!(d == Some(2147483647) || d == Some(2147483629));
+/// assert_eq!(algebra::factorization::squfof(1000000007 * 1000000007), Some(1000000007));
+/// assert_eq!(algebra::factorization::squfof(2305843009213693951), None);
+/// ```
+pub fn squfof(n: u64) -> Option<u64> {
+    if n < 4 || deterministic_millerrabin(n) {
+        return None;
+    }
+    for &p in [2, 3, 5, 7, 11].iter() {
+        if n.is_multiple_of(p) {
+            return Some(p);
+        }
+    }
+    if is_perfect_square(n) {
+        return Some(isqrt(n));
+    }
+
+    // Square free products of 3, 5, 7 and 11
+    const MULTIPLIERS: [u64; 16] = [1, 3, 5, 7, 11, 15, 21, 33, 35, 55, 77, 105, 165, 231, 385, 1155];
+    const STEPS: u64 = 1 << 8;
+    let mut forms: Vec<SquareForms> = MULTIPLIERS.iter().map(|&k| SquareForms::new(n, k)).collect();
+    while !forms.is_empty() {
+        for form in forms.iter_mut() {
+            if let Some(d) = form.advance(n, STEPS) {
+                return Some(d);
+            }
+        }
+        forms.retain(|form| !form.exhausted());
+    }
+
+    None
+}
+
+/// Forward cycle of SQUFOF for kn
+struct SquareForms {
+    kn: u128,
+    p0: i64,
+    p: i64,
+    q_prev: i64,
+    q: i64,
+    i: u64,
+    bound: u64,
+}
+
+impl SquareForms {
+    fn new(n: u64, k: u64) -> SquareForms {
+        let kn = k as u128 * n as u128;
+        let p0 = isqrt_u128(kn);
+        SquareForms {
+            kn,
+            p0: p0 as i64,
+            p: p0 as i64,
+            q_prev: 1,
+            q: (kn - p0 * p0) as i64,
+            // Index of q in the sequence Q_0, Q_1, ...
+            i: 1,
+            // 3 * 2 * sqrt(2 * sqrt(kn)) steps cover the cycle with room to spare
+            bound: 6 * isqrt_u128(2 * p0) as u64,
+        }
+    }
+
+    fn exhausted(&self) -> bool {
+        self.i >= self.bound
+    }
+
+    /// Takes up to `steps` steps of the forward cycle, returning a factor of n if one was found
+    fn advance(&mut self, n: u64, steps: u64) -> Option<u64> {
+        if self.q == 0 {
+            // kn is a square, so n shares a factor with sqrt(kn)
+            self.i = self.bound;
+            let d = gcd(self.p0 as u64, n);
+            return if d > 1 && d < n { Some(d) } else { None };
+        }
+
+        for _ in 0..steps {
+            if self.exhausted() {
+                return None;
+            }
+            let b = (self.p0 + self.p) / self.q;
+            let p = b * self.q - self.p;
+            let q = self.q_prev + b * (self.p - p);
+            self.p = p;
+            self.q_prev = self.q;
+            self.q = q;
+            self.i += 1;
+
+            if self.i.is_multiple_of(2) && is_perfect_square(q as u64) {
+                if let Some(d) = self.reverse(n) {
+                    return Some(d);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Cycle from the square root of the current square form until P repeats
+    fn reverse(&self, n: u64) -> Option<u64> {
+        let r = isqrt(self.q as u64) as i64;
+        let b = (self.p0 - self.p) / r;
+        let mut p = b * r + self.p;
+        let mut q_prev = r;
+        let mut q = ((self.kn - (p as u128) * (p as u128)) / r as u128) as i64;
+        loop {
+            let b = (self.p0 + p) / q;
+            let p_next = b * q - p;
+            if p_next == p {
+                break;
+            }
+            let q_next = q_prev + b * (p - p_next);
+            p = p_next;
+            q_prev = q;
+            q = q_next;
+        }
+
+        let d = gcd(p as u64, n);
+        if d > 1 && d < n {
+            Some(d)
+        } else {
+            None
+        }
+    }
+}
+
+/// Pollard's rho algorithm with Brent's cycle detection
+///
+/// Returns a non-trivial factor of a composite n in expected O(n^(1/4)).
+/// Prime n has no such factor and is returned as is.
+/// ```
+/// let n = 1000000007 * 998244353;
+/// let d = algebra::factorization::pollard_rho(n);
+/// assert!(d == 1000000007 || d == 998244353);
+/// ```
+pub fn pollard_rho(n: u64) -> u64 {
+    if n.is_multiple_of(2) {
+        return 2;
+    }
+    if deterministic_millerrabin(n) {
+        return n;
+    }
+
+    for c in 1..n {
+        if let Some(d) = rho_with(n, c, u64::MAX) {
+            return d;
+        }
+    }
+
+    n
+}
+
+/// Pollard's p - 1 method
+///
+/// Finds a prime factor p of n when p - 1 is b1-smooth apart from at most one prime in (b1, b2].
+/// Stage 1 raises 2 to every prime power up to b1, stage 2 tries each remaining prime in turn.
+/// Returns None if no factor was found with these bounds, and for prime and n < 4.
 /// ```
-/// assert_eq!(algebra::factorization::fermat_factorization(49), 7);
-/// assert_eq!(algebra::factorization::fermat_factorization(56), 4);
+/// let p = 4958119163; // p - 1 = 2 * 59 * 61 * 821 * 839
+/// let q = 1000012679;
+/// assert_eq!(algebra::factorization::pollard_pm1(p * q, 1000, 1000), Some(p));
+///
+/// let p = 5745712187; // p - 1 = 2 * 79 * 727 * 50021
+/// assert_eq!(algebra::factorization::pollard_pm1(p * q, 1000, 100000), Some(p));
+/// assert_eq!(algebra::factorization::pollard_pm1(p * q, 1000, 1000), None);
+///
+/// let p = 1000667; // p - 1 = 2 * 500333
+/// assert_eq!(algebra::factorization::pollard_pm1(p * q, 1, 1000000), Some(p));
+///
+/// assert_eq!(algebra::factorization::pollard_pm1(0, 1000, 1000), None);
+/// assert_eq!(algebra::factorization::pollard_pm1(2, 1000, 1000), None);
+/// assert_eq!(algebra::factorization::pollard_pm1(q, 1000, 1000), None);
+/// assert_eq!(algebra::factorization::pollard_pm1(2 * q, 1000, 1000), Some(2));
 /// ```
-pub fn fermat_factorization(n: u32) -> u32 {
-    let mut a: u32 = (n as f64).sqrt().ceil() as u32;
-    let mut b2: u32 = a * a - n;
-    let mut b: u32 = (b2 as f64).sqrt().round() as u32;
+pub fn pollard_pm1(n: u64, b1: u64, b2: u64) -> Option<u64> {
+    if n < 4 || deterministic_millerrabin(n) {
+        return None;
+    }
+    if n.is_multiple_of(2) {
+        return Some(2);
+    }
+    pm1_with(&MontgomeryRing::new(n as u128), b1, b2).map(|d| d as u64)
+}
 
-    while b * b != b2 {
-        a = a + 1;
-        b2 = a * a - n;
-        b = (b2 as f64).sqrt().round() as u32;
+/// Pollard's p - 1 method for big integers
+///
+/// Same as [`pollard_pm1`]
+/// ```
+/// use num::BigInt;
+///
+/// let p: BigInt = "76767643838365575287".parse().unwrap();
+/// let q: BigInt = "1000000000000000012501".parse().unwrap();
+/// assert_eq!(algebra::factorization::pollard_pm1_bigint(&(&p * &q), 1000, 1000), Some(p));
+/// ```
+pub fn pollard_pm1_bigint(n: &BigInt, b1: u64, b2: u64) -> Option<BigInt> {
+    if *n < BigInt::from(4) || millerrabin_bigint(n) {
+        return None;
+    }
+    if n.is_even() {
+        return Some(BigInt::from(2));
     }
+    pm1_with(&BigRing::new(n), b1, b2)
+}
 
-    a - b
+fn pm1_with<R: Ring>(ring: &R, b1: u64, b2: u64) -> Option<R::Int> {
+    // Stage 2 steps between odd primes, so 2 always belongs to stage 1
+    let b1 = cmp::max(b1, 2);
+    let sieve = BitSieve::new(cmp::max(b1, b2) as usize);
+    let one = ring.elem(1);
+    let mut a = ring.elem(2);
+    for p in sieve.iter().take_while(|&p| p as u64 <= b1) {
+        a = ring::pow(ring, &a, prime_power_below(p as u64, b1));
+    }
+    if let Some(d) = ring.factor(&ring.sub(&a, &one)) {
+        return Some(d);
+    }
+
+    // Stage 2, stepping a^s from prime to prime by multiplying with a^gap
+    let mut acc = one.clone();
+    let mut gap_powers = vec![one.clone()];
+    let mut last = 0;
+    let mut x = one.clone();
+    for s in sieve.iter().skip_while(|&p| p as u64 <= b1) {
+        let s = s as u64;
+        if last == 0 {
+            x = ring::pow(ring, &a, s);
+            gap_powers.push(ring.mul(&a, &a));
+        } else {
+            let gap = ((s - last) / 2) as usize;
+            while gap_powers.len() <= gap {
+                let next = ring.mul(&gap_powers[gap_powers.len() - 1], &gap_powers[1]);
+                gap_powers.push(next);
+            }
+            x = ring.mul(&x, &gap_powers[gap]);
+        }
+        acc = ring.mul(&acc, &ring.sub(&x, &one));
+        last = s;
+    }
+
+    ring.factor(&acc)
+}
+
+/// Williams' p + 1 method
+///
+/// Finds a prime factor p of n when p + 1 is b1-smooth.
+/// Works with Lucas sequences V_k(A), which only land in the subgroup of order p + 1
+/// when A^2 - 4 is a non-residue mod p, so a few seeds A are tried.
+/// Returns None if no seed found a factor, and for prime and n < 4.
+/// ```
+/// let p = 1020069781; // p + 1 = 2 * 739 * 751 * 919
+/// let q = 1000012679;
+/// assert_eq!(algebra::factorization::williams_pp1(p * q, 1000), Some(p));
+/// assert_eq!(algebra::factorization::williams_pp1(2, 1000), None);
+/// assert_eq!(algebra::factorization::williams_pp1(q, 1000), None);
+/// ```
+pub fn williams_pp1(n: u64, b1: u64) -> Option<u64> {
+    if n < 4 || deterministic_millerrabin(n) {
+        return None;
+    }
+    if n.is_multiple_of(2) {
+        return Some(2);
+    }
+    pp1_with(&MontgomeryRing::new(n as u128), b1).map(|d| d as u64)
+}
+
+/// Williams' p + 1 method for big integers
+///
+/// Same as [`williams_pp1`]
+/// ```
+/// use num::BigInt;
+///
+/// let p: BigInt = "38096379847298533153".parse().unwrap();
+/// let q: BigInt = "1000000000000000012501".parse().unwrap();
+/// assert_eq!(algebra::factorization::williams_pp1_bigint(&(&p * &q), 1000), Some(p));
+/// ```
+pub fn williams_pp1_bigint(n: &BigInt, b1: u64) -> Option<BigInt> {
+    if *n < BigInt::from(4) || millerrabin_bigint(n) {
+        return None;
+    }
+    if n.is_even() {
+        return Some(BigInt::from(2));
+    }
+    pp1_with(&BigRing::new(n), b1)
+}
+
+fn pp1_with<R: Ring>(ring: &R, b1: u64) -> Option<R::Int> {
+    const SEEDS: [u64; 6] = [3, 5, 7, 11, 13, 17];
+    let sieve = BitSieve::new(b1 as usize);
+    let two = ring.elem(2);
+    for &seed in SEEDS.iter() {
+        let mut v = ring.elem(seed);
+        for p in sieve.iter() {
+            v = lucas_v(ring, &v, prime_power_below(p as u64, b1));
+        }
+        if let Some(d) = ring.factor(&ring.sub(&v, &two)) {
+            return Some(d);
+        }
+    }
+
+    None
+}
+
+/// V_k(A) from V_1 = A using V_2k = V_k^2 - 2 and V_2k+1 = V_k * V_k+1 - A
+fn lucas_v<R: Ring>(ring: &R, a: &R::Elem, k: u64) -> R::Elem {
+    let two = ring.elem(2);
+    let mut x = a.clone();
+    let mut y = ring.sub(&ring.mul(a, a), &two);
+    for bit in (0..63 - k.leading_zeros()).rev() {
+        let xy = ring.sub(&ring.mul(&x, &y), a);
+        if (k >> bit) & 1 == 1 {
+            x = xy;
+            y = ring.sub(&ring.mul(&y, &y), &two);
+        } else {
+            y = xy;
+            x = ring.sub(&ring.mul(&x, &x), &two);
+        }
+    }
+
+    x
+}
+
+/// Largest power of the prime p not above b
+pub(crate) fn prime_power_below(p: u64, b: u64) -> u64 {
+    let mut pk = p;
+    while pk <= b / p {
+        pk *= p;
+    }
+
+    pk
+}
+
+/// Brent's rho with the polynomial x^2 + c, giving up after about `max_steps` steps
+///
+/// Returns None when the cycle closes mod n before a factor turns up, or the steps run out
+pub(crate) fn rho_with(n: u64, c: u64, max_steps: u64) -> Option<u64> {
+    // Checking gcd only once per batch of M steps
+    const M: u64 = 128;
+    // x^2 + c summed in 128 bits, as it can pass 2^64 for n close to it
+    let f = |x: u64| ((mul_mod(x, x, n) as u128 + c as u128) % n as u128) as u64;
+    let mut x = 2;
+    let mut y = 2;
+    let mut ys = 2;
+    let mut q = 1;
+    let mut g = 1;
+    let mut r = 1;
+    while g == 1 {
+        if r > max_steps {
+            return None;
+        }
+        x = y;
+        for _ in 0..r {
+            y = f(y);
+        }
+        let mut k = 0;
+        while k < r && g == 1 {
+            ys = y;
+            for _ in 0..cmp::min(M, r - k) {
+                y = f(y);
+                q = mul_mod(q, x.abs_diff(y), n);
+            }
+            g = gcd(q, n);
+            k += M;
+        }
+        r *= 2;
+    }
+    if g == n {
+        // The batch overshot, step back one at a time
+        loop {
+            ys = f(ys);
+            g = gcd(x.abs_diff(ys), n);
+            if g > 1 {
+                break;
+            }
+        }
+    }
+
+    if g == n {
+        None
+    } else {
+        Some(g)
+    }
+}
+
+/// Complete factorization of n
+///
+/// Small factors are removed by the wheel, then Miller Rabin and Pollard's rho split the rest.
+/// Returns prime factors in increasing order, with multiplicity.
+/// ```
+/// assert_eq!(algebra::factorization::factorize(60), vec![2, 2, 3, 5]);
+/// assert_eq!(algebra::factorization::factorize(1000000007 * 998244353), vec![998244353, 1000000007]);
+/// assert_eq!(algebra::factorization::factorize(18446744073709551615), vec![3, 5, 17, 257, 641, 65537, 6700417]);
+/// ```
+pub fn factorize(n: u64) -> Vec<u64> {
+    factorize_with(n, FactorMethod::PollardRho)
+}
+
+/// Method used by [`factorize_with`] to split composites without small factors
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FactorMethod {
+    /// [`trial_division_wheel`]
+    TrialDivision,
+    /// [`pollard_rho`]
+    PollardRho,
+    /// [`squfof`], falling back to Pollard's rho on the rare inputs it can't split
+    Squfof,
+    /// [`ecm_u128`] with bounds raised until a factor is found,
+    /// or [`pollard_rho`] below 2^40 and when ECM gives up
+    Ecm,
+}
+
+/// Complete factorization of n, splitting composites with the given method
+///
+/// Same as [`factorize`]
+/// ```
+/// use algebra::factorization::{factorize_with, FactorMethod};
+///
+/// let n = 2147483647 * 2147483629;
+/// assert_eq!(factorize_with(n, FactorMethod::Squfof), vec![2147483629, 2147483647]);
+/// assert_eq!(factorize_with(n, FactorMethod::PollardRho), vec![2147483629, 2147483647]);
+/// assert_eq!(factorize_with(n, FactorMethod::Ecm), vec![2147483629, 2147483647]);
+/// assert_eq!(factorize_with(2 * 5839 * 12197, FactorMethod::Ecm), vec![2, 5839, 12197]);
+/// assert_eq!(factorize_with(1024 * 3 * 1000003 * 1000033, FactorMethod::Squfof).len(), 13);
+/// assert_eq!(factorize_with(1000003 * 1000033, FactorMethod::TrialDivision), vec![1000003, 1000033]);
+/// ```
+pub fn factorize_with(mut n: u64, method: FactorMethod) -> Vec<u64> {
+    let mut factorization = wheel_division(&mut n, SMALL_FACTOR_BOUND);
+    split_composites(n, &mut factorization, |m| split_with(m, method));
+    factorization.sort_unstable();
+
+    factorization
+}
+
+/// A non-trivial factor of a composite n without factors below [`SMALL_FACTOR_BOUND`]
+fn split_with(n: u64, method: FactorMethod) -> u64 {
+    match method {
+        FactorMethod::TrialDivision => trial_division_wheel(n)[0],
+        FactorMethod::PollardRho => pollard_rho(n),
+        FactorMethod::Squfof => squfof(n).unwrap_or_else(|| pollard_rho(n)),
+        FactorMethod::Ecm => split_ecm(n),
+    }
+}
+
+/// Pollard's rho below 2^40, otherwise ECM with bounds for ever larger factors and rho if it gives up
+pub(crate) fn split_ecm(n: u64) -> u64 {
+    // Rho needs at most about 2^10 steps here, less than setting up a single curve
+    if n < 1 << 40 {
+        return pollard_rho(n);
+    }
+    // ECM is hopeless on a prime power, but its root is a factor
+    if let Some((base, _)) = perfect_power(n) {
+        return base;
+    }
+    split_bigint(&BigInt::from(n)).map_or_else(|| pollard_rho(n), |d| d.to_u64().unwrap())
+}
+
+/// Pushes the prime factors of n to `factorization`, using `split` to find a non-trivial factor
+/// of every composite met, all of which are free of factors below [`SMALL_FACTOR_BOUND`]
+pub(crate) fn split_composites<F>(n: u64, factorization: &mut Vec<u64>, mut split: F)
+where
+    F: FnMut(u64) -> u64,
+{
+    let mut composites = vec![n];
+    while let Some(m) = composites.pop() {
+        if m <= 1 {
+            continue;
+        }
+        if m < SMALL_FACTOR_BOUND * SMALL_FACTOR_BOUND || deterministic_millerrabin(m) {
+            factorization.push(m);
+        } else {
+            let d = split(m);
+            composites.push(d);
+            composites.push(m / d);
+        }
+    }
+}
+
+/// Non-trivial factor of a composite n that isn't a perfect power
+///
+/// ECM looks for small factors first, with bounds for ever larger ones up to [`MAX_ECM_DIGITS`].
+/// Numbers of [`SIQS_DIGITS`] digits or more go to the quadratic sieve once ECM has spent a little effort.
+/// None if neither finds a factor.
+fn split_bigint(n: &BigInt) -> Option<BigInt> {
+    let digits = n.to_string().len() as u32;
+    for ecm_digits in (15..=MAX_ECM_DIGITS).step_by(5) {
+        if digits >= SIQS_DIGITS && ecm_digits == 25 {
+            if let Some(d) = siqs(n) {
+                return Some(d);
+            }
+        }
+        let params = EcmParams::for_digits(ecm_digits);
+        let d = match n.to_u128() {
+            Some(n) => ecm_u128(n, &params).map(BigInt::from),
+            None => ecm(n, &params),
+        };
+        if d.is_some() {
+            return d;
+        }
+    }
+
+    None
+}
+
+/// Largest factor size ECM is tuned for in [`split_bigint`], its sieve up to b2 takes about 10 MB
+const MAX_ECM_DIGITS: u32 = 40;
+
+/// Size from which [`factorize_bigint`] switches from ECM to the quadratic sieve
+const SIQS_DIGITS: u32 = 40;
+
+/// Bound below which [`factorize`] finds factors by trial division
+pub(crate) const SMALL_FACTOR_BOUND: u64 = 1 << 10;
+
+/// Complete factorization of a big integer n >= 1
+///
+/// Small factors go by trial division and anything that fits in 64 bits by [`factorize`].
+/// Larger composites are split by ECM, with bounds raised for factors of up to 40 digits,
+/// and by the quadratic sieve from 40 digits on. A composite neither of them splits is kept whole.
+/// Returns the factors in increasing order, with multiplicity.
+/// ```
+/// use num::BigInt;
+///
+/// let n: BigInt = "18446744073709551617".parse().unwrap(); // 2^64 + 1
+/// let factors = vec![BigInt::from(274177), BigInt::from(67280421310721_u64)];
+/// assert_eq!(algebra::factorization::factorize_bigint(&n), factors);
+///
+/// let q: BigInt = "170141183460469231731687303715884105727".parse().unwrap(); // 2^127 - 1
+/// let n = &q * 1000000007 * 6;
+/// let factors = vec![BigInt::from(2), BigInt::from(3), BigInt::from(1000000007), q];
+/// assert_eq!(algebra::factorization::factorize_bigint(&n), factors);
+/// ```
+pub fn factorize_bigint(n: &BigInt) -> Vec<BigInt> {
+    assert!(n.is_positive(), "can only factorize positive integers");
+    let mut factorization = Vec::new();
+    let mut n = n.clone();
+    for d in 2..SMALL_FACTOR_BOUND {
+        let d = BigInt::from(d);
+        while (&n % &d).is_zero() {
+            n /= &d;
+            factorization.push(d.clone());
+        }
+    }
+
+    let mut composites = vec![n];
+    while let Some(m) = composites.pop() {
+        if m.is_one() {
+            continue;
+        }
+        if let Some(m) = m.to_u64() {
+            factorization.extend(factorize(m).into_iter().map(BigInt::from));
+            continue;
+        }
+        if millerrabin_bigint(&m) {
+            factorization.push(m);
+            continue;
+        }
+        if let Some((base, exp)) = perfect_power_bigint(&m) {
+            composites.extend(std::iter::repeat_n(base, exp as usize));
+            continue;
+        }
+
+        match split_bigint(&m) {
+            Some(d) => {
+                composites.push(&m / &d);
+                composites.push(d);
+            }
+            None => factorization.push(m),
+        }
+    }
+    factorization.sort();
+
+    factorization
 }
@@ -0,0 +1,265 @@
+//! Interchangeable factorization strategies behind a common [`Factorizer`] trait
+//!
+//! Every strategy returns the complete factorization. They differ in how composites are split,
+//! which decides how fast they are on a given kind of input.
+use std::cmp;
+
+use crate::factorization::{
+    factorize_with, rho_with, split_composites, split_ecm, squfof, trial_division,
+    trial_division_primes_precomputed, trial_division_wheel, wheel_division, FactorMethod, SMALL_FACTOR_BOUND,
+};
+use crate::prime::primes;
+use crate::roots::iroot;
+
+/// Prime factorization as (prime, exponent) pairs with increasing primes
+/// ```
+/// use algebra::factorizer::Factorization;
+///
+/// let f = Factorization::from_primes(&[2, 2, 3, 5, 5, 5]);
+/// assert_eq!(f.factors(), &[(2, 2), (3, 1), (5, 3)]);
+/// assert_eq!(f.value(), 1500);
+/// assert_eq!(f.to_vec(), vec![2, 2, 3, 5, 5, 5]);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Factorization {
+    factors: Vec<(u64, u32)>,
+}
+
+impl Factorization {
+    /// Groups the given primes, in any order and with multiplicity
+    pub fn from_primes(primes: &[u64]) -> Factorization {
+        let mut primes = primes.to_vec();
+        primes.sort_unstable();
+        let mut factors: Vec<(u64, u32)> = Vec::new();
+        for p in primes {
+            match factors.last_mut() {
+                Some((q, e)) if *q == p => *e += 1,
+                _ => factors.push((p, 1)),
+            }
+        }
+
+        Factorization { factors }
+    }
+
+    /// (prime, exponent) pairs with increasing primes
+    pub fn factors(&self) -> &[(u64, u32)] {
+        &self.factors
+    }
+
+    /// The factored number, 1 for the empty factorization
+    pub fn value(&self) -> u64 {
+        self.factors.iter().map(|&(p, e)| p.pow(e)).product()
+    }
+
+    /// Primes in increasing order, each repeated as often as it divides the number
+    pub fn to_vec(&self) -> Vec<u64> {
+        self.factors.iter().flat_map(|&(p, e)| (0..e).map(move |_| p)).collect()
+    }
+}
+
+/// Strategy for factoring integers
+///
+/// 0 and 1 have the empty factorization.
+/// ```
+/// use algebra::factorizer::{AutoFactorizer, Ecm, Factorizer, PollardRho, PrecomputedPrimes, Squfof, TrialDivision, Wheel};
+///
+/// let n = 600851475143;
+/// let factorizers: [&dyn Factorizer; 3] = [&TrialDivision, &PollardRho, &Squfof];
+/// for f in factorizers.iter() {
+///     assert_eq!(f.factor(n).to_vec(), vec![71, 839, 1471, 6857]);
+/// }
+///
+/// // The largest primes below 2^40 and 2^64, trial division only gets the first
+/// let p = 1099511627689;
+/// let factorizers: [&dyn Factorizer; 2] = [&TrialDivision, &Wheel];
+/// for f in factorizers.iter() {
+///     assert_eq!(f.factor(p).to_vec(), vec![p]);
+/// }
+/// let p = 18446744073709551557;
+/// let precomputed = PrecomputedPrimes::new(1000);
+/// let auto = AutoFactorizer::new();
+/// let factorizers: [&dyn Factorizer; 5] = [&precomputed, &PollardRho, &Squfof, &Ecm, &auto];
+/// for f in factorizers.iter() {
+///     assert_eq!(f.factor(p).to_vec(), vec![p]);
+/// }
+/// ```
+pub trait Factorizer {
+    fn factor(&self, n: u64) -> Factorization;
+}
+
+/// [`trial_division`](crate::factorization::trial_division)
+pub struct TrialDivision;
+
+impl Factorizer for TrialDivision {
+    fn factor(&self, n: u64) -> Factorization {
+        Factorization::from_primes(&trial_division(n))
+    }
+}
+
+/// [`trial_division_wheel`](crate::factorization::trial_division_wheel)
+pub struct Wheel;
+
+impl Factorizer for Wheel {
+    fn factor(&self, n: u64) -> Factorization {
+        Factorization::from_primes(&trial_division_wheel(n))
+    }
+}
+
+/// [`trial_division_primes_precomputed`](crate::factorization::trial_division_primes_precomputed)
+/// with the primes up to a limit
+///
+/// A cofactor too big to be shown prime by the table is split with Pollard's rho.
+/// ```
+/// use algebra::factorizer::{Factorizer, PrecomputedPrimes};
+///
+/// let f = PrecomputedPrimes::new(1000);
+/// assert_eq!(f.factor(2 * 997 * 997).to_vec(), vec![2, 997, 997]);
+/// assert_eq!(f.factor(1009 * 1013 * 1019).to_vec(), vec![1009, 1013, 1019]);
+/// ```
+pub struct PrecomputedPrimes {
+    primes: Vec<u64>,
+}
+
+impl PrecomputedPrimes {
+    pub fn new(limit: u64) -> PrecomputedPrimes {
+        PrecomputedPrimes {
+            primes: primes(limit as usize).into_iter().map(|p| p as u64).collect(),
+        }
+    }
+}
+
+impl Factorizer for PrecomputedPrimes {
+    fn factor(&self, n: u64) -> Factorization {
+        if n == 0 {
+            return Factorization::default();
+        }
+        let mut factorization = trial_division_primes_precomputed(n, &self.primes);
+        let largest = self.primes.last().map_or(1, |&p| p as u128);
+        if let Some(&m) = factorization.last() {
+            if m as u128 > largest * largest {
+                factorization.pop();
+                factorization.extend(factorize_with(m, FactorMethod::PollardRho));
+            }
+        }
+
+        Factorization::from_primes(&factorization)
+    }
+}
+
+/// [`pollard_rho`](crate::factorization::pollard_rho) after trial division of small factors
+pub struct PollardRho;
+
+impl Factorizer for PollardRho {
+    fn factor(&self, n: u64) -> Factorization {
+        factorization_with(n, FactorMethod::PollardRho)
+    }
+}
+
+/// [`squfof`](crate::factorization::squfof) after trial division of small factors
+pub struct Squfof;
+
+impl Factorizer for Squfof {
+    fn factor(&self, n: u64) -> Factorization {
+        factorization_with(n, FactorMethod::Squfof)
+    }
+}
+
+/// [`ecm_u128`](crate::ecm::ecm_u128) after trial division of small factors
+///
+/// Bounds are raised until each composite splits, composites below 2^40 go to Pollard's rho.
+/// ```
+/// use algebra::factorizer::{Ecm, Factorizer};
+///
+/// assert_eq!(Ecm.factor(1000003 * 1000000000039).to_vec(), vec![1000003, 1000000000039]);
+/// assert_eq!(Ecm.factor(2 * 5839 * 12197).to_vec(), vec![2, 5839, 12197]);
+/// ```
+pub struct Ecm;
+
+impl Factorizer for Ecm {
+    fn factor(&self, n: u64) -> Factorization {
+        factorization_with(n, FactorMethod::Ecm)
+    }
+}
+
+fn factorization_with(n: u64, method: FactorMethod) -> Factorization {
+    Factorization::from_primes(&factorize_with(n, method))
+}
+
+/// Picks the method by the size of each composite and falls back when it fails
+///
+/// Factors below 2^10 are found by trial division. Composites left below 2^62 go to SQUFOF,
+/// larger ones or those SQUFOF gives up on get a bounded run of Pollard's rho,
+/// and ECM finishes whatever is still unsplit.
+/// An observer can be given to learn which method split off each factor.
+/// ```
+/// use std::cell::RefCell;
+/// use algebra::factorization::FactorMethod;
+/// use algebra::factorizer::{AutoFactorizer, Factorizer};
+///
+/// assert_eq!(AutoFactorizer::new().factor(18446744073709551615).to_vec(), vec![3, 5, 17, 257, 641, 65537, 6700417]);
+///
+/// let found = RefCell::new(Vec::new());
+/// let auto = AutoFactorizer::with_observer(|d, method| found.borrow_mut().push((d, method)));
+/// auto.factor(12 * 1000003 * 1000033);
+/// let found = found.into_inner();
+/// assert_eq!(found[..3], [(2, FactorMethod::TrialDivision), (2, FactorMethod::TrialDivision), (3, FactorMethod::TrialDivision)]);
+/// assert!(found[3] == (1000003, FactorMethod::Squfof) || found[3] == (1000033, FactorMethod::Squfof));
+/// ```
+pub struct AutoFactorizer<O = fn(u64, FactorMethod)> {
+    observer: O,
+}
+
+impl AutoFactorizer {
+    pub fn new() -> AutoFactorizer {
+        AutoFactorizer { observer: |_, _| {} }
+    }
+}
+
+impl Default for AutoFactorizer {
+    fn default() -> AutoFactorizer {
+        AutoFactorizer::new()
+    }
+}
+
+impl<O: Fn(u64, FactorMethod)> AutoFactorizer<O> {
+    /// `observer(d, method)` is called whenever `method` splits off a factor d
+    pub fn with_observer(observer: O) -> AutoFactorizer<O> {
+        AutoFactorizer { observer }
+    }
+
+    fn split(&self, n: u64) -> u64 {
+        let (d, method) = self.split_with_method(n);
+        (self.observer)(d, method);
+
+        d
+    }
+
+    fn split_with_method(&self, n: u64) -> (u64, FactorMethod) {
+        if n < 1 << 62 {
+            if let Some(d) = squfof(n) {
+                return (d, FactorMethod::Squfof);
+            }
+        }
+        // Rho needs about sqrt(p) <= n^(1/4) steps, allow a few times that
+        let max_steps = cmp::max(8 * iroot(n, 4), 1 << 12);
+        for c in 1..4 {
+            if let Some(d) = rho_with(n, c, max_steps) {
+                return (d, FactorMethod::PollardRho);
+            }
+        }
+
+        (split_ecm(n), FactorMethod::Ecm)
+    }
+}
+
+impl<O: Fn(u64, FactorMethod)> Factorizer for AutoFactorizer<O> {
+    fn factor(&self, mut n: u64) -> Factorization {
+        let mut factorization = wheel_division(&mut n, SMALL_FACTOR_BOUND);
+        for &p in factorization.iter() {
+            (self.observer)(p, FactorMethod::TrialDivision);
+        }
+        split_composites(n, &mut factorization, |m| self.split(m));
+
+        Factorization::from_primes(&factorization)
+    }
+}
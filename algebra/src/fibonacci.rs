@@ -1,25 +1,232 @@
 //! [Fibonacci](https://cp-algorithms.com/algebra/fibonacci-numbers.html)
+use num::{BigInt, One, Zero};
+use std::convert::TryFrom;
+
+use crate::binary_exponentiation::mul_mod;
+use crate::factorizer::{AutoFactorizer, Factorizer};
 
 /// Fast Doubling method
 /// Caclulates fib(n) in O(log n)
+///
+/// Returns (F(n), F(n + 1)), which overflow for n >= 92.
+/// The bits of n are walked from the top, so there's no recursion.
 /// ```
 /// let n = 4;
 /// let (n4, n5) = algebra::fibonacci::fibonacci(n);
 /// assert_eq!(n4, 3);
 /// assert_eq!(n5, 5);
+///
+/// assert_eq!(algebra::fibonacci::fibonacci(0), (0, 1));
+/// assert_eq!(algebra::fibonacci::fibonacci(91), (4660046610375530309, 7540113804746346429));
 /// ```
 pub fn fibonacci(n: i64) -> (i64, i64) {
-    if n == 0 {
-        (0, 1)
-    } else {
-        let (k, k1) = fibonacci(n >> 1);
+    let (mut k, mut k1) = (0, 1);
+    for i in (0..bit_length(n as u64)).rev() {
+        // F(2k) = F(k) (2 F(k + 1) - F(k)) and F(2k + 1) = F(k)^2 + F(k + 1)^2
         let c = k * (2 * k1 - k);
-        let d = k1*k1 + k * k;
+        let d = k1 * k1 + k * k;
+        if (n >> i) & 1 == 1 {
+            k = d;
+            k1 = c + d;
+        } else {
+            k = c;
+            k1 = d;
+        }
+    }
+
+    (k, k1)
+}
+
+fn bit_length(n: u64) -> u32 {
+    64 - n.leading_zeros()
+}
 
-        if n & 1 == 1 {
-            (d, c + d)
+/// (F(n), F(n + 1)) mod m by fast doubling
+///
+/// Takes n in 128 bits, as candidate Pisano periods can pass 2^64.
+fn fibonacci_pair_mod(n: u128, m: u64) -> (u64, u64) {
+    let (mut k, mut k1) = (0, 1 % m);
+    for i in (0..128 - n.leading_zeros()).rev() {
+        let c = mul_mod(k, ((2 * k1 as u128 + m as u128 - k as u128) % m as u128) as u64, m);
+        let d = ((mul_mod(k1, k1, m) as u128 + mul_mod(k, k, m) as u128) % m as u128) as u64;
+        if (n >> i) & 1 == 1 {
+            k = d;
+            k1 = ((c as u128 + d as u128) % m as u128) as u64;
         } else {
-            (c, d)
+            k = c;
+            k1 = d;
         }
     }
+
+    (k, k1)
+}
+
+/// F(n) mod m for m >= 1 in O(log n)
+///
+/// Products are taken in 128 bits, so any 64 bit modulus works.
+/// ```
+/// use algebra::fibonacci::fibonacci_mod;
+///
+/// assert_eq!(fibonacci_mod(10, 1000), 55);
+/// assert_eq!(fibonacci_mod(100, 1000000007), 687995182);
+/// assert_eq!(fibonacci_mod(18446744073709551615, 18446744073709551557), 18446743708274255395);
+/// assert_eq!(fibonacci_mod(5, 1), 0);
+/// ```
+pub fn fibonacci_mod(n: u64, m: u64) -> u64 {
+    fibonacci_pair_mod(n as u128, m).0
+}
+
+/// (F(n), F(n + 1)) by fast doubling
+fn fibonacci_pair_big(n: u64) -> (BigInt, BigInt) {
+    let (mut k, mut k1) = (BigInt::zero(), BigInt::one());
+    for i in (0..bit_length(n)).rev() {
+        let c = &k * (&k1 * 2 - &k);
+        let d = &k1 * &k1 + &k * &k;
+        if (n >> i) & 1 == 1 {
+            k1 = &c + &d;
+            k = d;
+        } else {
+            k = c;
+            k1 = d;
+        }
+    }
+
+    (k, k1)
+}
+
+/// F(n) exactly
+/// ```
+/// use num::BigInt;
+/// use algebra::fibonacci::fibonacci_big;
+///
+/// assert_eq!(fibonacci_big(0), BigInt::from(0));
+/// assert_eq!(fibonacci_big(93), BigInt::from(12200160415121876738u64));
+/// assert_eq!(fibonacci_big(100).to_string(), "354224848179261915075");
+/// assert_eq!(fibonacci_big(10000).to_string().len(), 2090);
+/// ```
+pub fn fibonacci_big(n: u64) -> BigInt {
+    fibonacci_pair_big(n).0
+}
+
+/// Lucas number L(n) = 2 F(n + 1) - F(n), which overflows for n >= 91
+///
+/// L(0) = 2, L(1) = 1 and L(n + 2) = L(n + 1) + L(n).
+/// ```
+/// use algebra::fibonacci::lucas;
+///
+/// assert_eq!(lucas(0), 2);
+/// assert_eq!(lucas(1), 1);
+/// assert_eq!(lucas(10), 123);
+/// assert_eq!(lucas(90), 6440026026380244498);
+/// ```
+pub fn lucas(n: i64) -> i64 {
+    let (k, k1) = fibonacci(n);
+    k1 + (k1 - k)
+}
+
+/// L(n) mod m for m >= 1 in O(log n)
+/// ```
+/// use algebra::fibonacci::lucas_mod;
+///
+/// assert_eq!(lucas_mod(10, 100), 23);
+/// assert_eq!(lucas_mod(100, 1000000007), 876413006);
+/// ```
+pub fn lucas_mod(n: u64, m: u64) -> u64 {
+    let (k, k1) = fibonacci_pair_mod(n as u128, m);
+    ((2 * k1 as u128 + m as u128 - k as u128) % m as u128) as u64
+}
+
+/// L(n) exactly
+/// ```
+/// use algebra::fibonacci::lucas_big;
+///
+/// assert_eq!(lucas_big(100).to_string(), "792070839848372253127");
+/// ```
+pub fn lucas_big(n: u64) -> BigInt {
+    let (k, k1) = fibonacci_pair_big(n);
+    k1 * 2 - k
+}
+
+/// Pisano period π(m), the period of F(n) mod m
+///
+/// π is taken over the prime powers of m and combined by lcm. For a prime p, π(p) divides
+/// p - 1 when p = ±1 (mod 10), 2(p + 1) when p = ±3 (mod 10), and π(5) = 20. Prime factors
+/// are stripped from that bound while F(t), F(t + 1) stays 0, 1 mod p, like finding a
+/// multiplicative order. π(p^k) divides p^(k - 1) π(p) and is found the same way.
+/// The candidates are worked out in 128 bits, so only the final period has to fit.
+///
+/// # Panics
+/// If m = 0, or if π(m) doesn't fit in a u64, which needs m > 2^64 / 6 as π(m) <= 6m
+/// ```
+/// use algebra::fibonacci::{fibonacci_mod, pisano_period};
+///
+/// assert_eq!(pisano_period(1), 1);
+/// assert_eq!(pisano_period(2), 3);
+/// assert_eq!(pisano_period(10), 60);
+/// assert_eq!(pisano_period(1000), 1500);
+/// assert_eq!(pisano_period(1000000007), 2000000016);
+/// assert_eq!(pisano_period(1000000009), 333333336);
+/// assert_eq!(pisano_period(3 * 3 * 3 * 7 * 7), 1008);
+///
+/// // 2(p + 1) is past 2^64, but the period is a third of it
+/// let p = 9223372036854776243;
+/// assert_eq!(pisano_period(p), 6148914691236517496);
+/// assert_eq!(fibonacci_mod(pisano_period(p), p), 0);
+///
+/// // Reduces huge indices
+/// let m = 1000000007;
+/// let n = 18446744073709551615;
+/// assert_eq!(fibonacci_mod(n, m), fibonacci_mod(n % pisano_period(m), m));
+/// ```
+pub fn pisano_period(m: u64) -> u64 {
+    assert!(m >= 1, "the Pisano period needs m >= 1");
+
+    // Below 6 * 2^64, so the lcm can't overflow 128 bits
+    let period = AutoFactorizer::new()
+        .factor(m)
+        .factors()
+        .iter()
+        .map(|&(p, k)| pisano_period_prime_power(p, k))
+        .fold(1, |a: u128, b| a / gcd_u128(a, b) * b);
+
+    u64::try_from(period).expect("Pisano period overflows u64")
+}
+
+fn gcd_u128(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        a %= b;
+        std::mem::swap(&mut a, &mut b);
+    }
+
+    a
+}
+
+/// π(p^k), which can be past 2^64 when 2(p + 1) is
+fn pisano_period_prime_power(p: u64, k: u32) -> u128 {
+    let is_period = |t: u128, m: u64| fibonacci_pair_mod(t, m) == (0, 1 % m);
+
+    // The bound as a u64 times a prime multiplier, or 1
+    let (base, multiplier) = match p % 10 {
+        1 | 9 => (p - 1, 1),
+        5 => (4, 5),
+        _ => (p + 1, 2),
+    };
+    let mut t = base as u128 * multiplier as u128;
+    let factors = AutoFactorizer::new().factor(base);
+    let primes = factors.factors().iter().map(|&(q, _)| q).chain(Some(multiplier).filter(|&q| q > 1));
+    for q in primes {
+        let q = q as u128;
+        while t.is_multiple_of(q) && is_period(t / q, p) {
+            t /= q;
+        }
+    }
+
+    let pisano_p = t;
+    let pk = p.pow(k);
+    t *= (p as u128).pow(k - 1);
+    while t != pisano_p && is_period(t / p as u128, pk) {
+        t /= p as u128;
+    }
+
+    t
 }
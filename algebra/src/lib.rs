@@ -6,12 +6,28 @@ pub mod binary_exponentiation;
 pub mod geometric_progression;
 pub mod gcd;
 pub mod linear_diophantine;
+pub mod linear_congruence;
 pub mod fibonacci;
+pub mod linear_recurrence;
 pub mod prime;
+pub mod multiplicative_function;
+pub mod roots;
 pub mod primality;
 pub mod factorization;
+pub mod factorizer;
+pub mod divisors;
+pub mod totient;
+pub mod primitive_root;
+pub mod discrete_log;
+pub mod ecm;
+pub mod qs;
+mod ring;
 pub mod modular_inverse;
+pub mod combinatorics;
+pub mod modular_sqrt;
 pub mod garners_algorithm;
+#[cfg(feature = "parallel")]
+pub mod parallel;
 
 pub mod lcm {
     pub use super::gcd::gcd;
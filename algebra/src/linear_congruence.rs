@@ -0,0 +1,67 @@
+//! [Linear Congruences](https://cp-algorithms.com/algebra/linear_congruence_equation.html) and
+//! the [generalized Chinese Remainder Theorem](https://cp-algorithms.com/algebra/chinese-remainder-theorem.html)
+//!
+//! Solution sets are returned as (base, step) with base in [0, step),
+//! standing for every x = base + k step.
+use crate::binary_exponentiation::mul_mod;
+use crate::gcd::gcd;
+use crate::modular_inverse::modular_inverse_u64;
+
+/// All x with ax = b (mod m), for m >= 1
+///
+/// With g = gcd(a, m) there are solutions only when g divides b, and then they repeat
+/// every m / g. None is returned when there are none.
+/// ```
+/// use algebra::linear_congruence::solve_linear_congruence;
+///
+/// assert_eq!(solve_linear_congruence(3, 4, 7), Some((6, 7)));
+/// assert_eq!(solve_linear_congruence(6, 4, 10), Some((4, 5)));
+/// assert_eq!(solve_linear_congruence(6, 3, 10), None);
+/// assert_eq!(solve_linear_congruence(0, 0, 10), Some((0, 1)));
+///
+/// let m = 18446744073709551557;
+/// let (x, step) = solve_linear_congruence(m - 2, 5, m).unwrap();
+/// assert_eq!(step, m);
+/// assert_eq!(algebra::binary_exponentiation::mul_mod(m - 2, x, m), 5);
+/// ```
+pub fn solve_linear_congruence(a: u64, b: u64, m: u64) -> Option<(u64, u64)> {
+    let a = a % m;
+    let b = b % m;
+    let g = gcd(a, m);
+    if !b.is_multiple_of(g) {
+        return None;
+    }
+    let step = m / g;
+    let x = mul_mod(b / g, modular_inverse_u64(a / g, step).unwrap(), step);
+
+    Some((x, step))
+}
+
+/// All x satisfying every a_i x = b_i (mod m_i) of the system, for m_i >= 1
+///
+/// Each congruence is reduced to x = r_i (mod n_i) and these are merged pairwise, moduli
+/// needn't be coprime. None is returned when the system is inconsistent.
+/// The empty system is solved by every x.
+///
+/// # Panics
+/// If the least common multiple of the reduced moduli doesn't fit in a u64
+/// ```
+/// use algebra::linear_congruence::solve_congruence_system;
+///
+/// assert_eq!(solve_congruence_system(&[(1, 2, 3), (1, 3, 5), (1, 2, 7)]), Some((23, 105)));
+/// assert_eq!(solve_congruence_system(&[(1, 3, 4), (1, 5, 6)]), Some((11, 12)));
+/// assert_eq!(solve_congruence_system(&[(1, 3, 4), (1, 4, 6)]), None);
+/// assert_eq!(solve_congruence_system(&[(2, 2, 4), (3, 0, 9)]), Some((3, 6)));
+/// assert_eq!(solve_congruence_system(&[]), Some((0, 1)));
+/// ```
+pub fn solve_congruence_system(congruences: &[(u64, u64, u64)]) -> Option<(u64, u64)> {
+    congruences.iter().try_fold((0, 1), |(r1, n1), &(a, b, m)| {
+        let (r2, n2) = solve_linear_congruence(a, b, m)?;
+        // x = r1 + n1 t with n1 t = r2 - r1 (mod n2)
+        let diff = ((r2 as u128 + n2 as u128 - (r1 % n2) as u128) % n2 as u128) as u64;
+        let (t, step) = solve_linear_congruence(n1, diff, n2)?;
+        let n = n1.checked_mul(step).expect("lcm of the moduli overflows u64");
+
+        Some(((r1 as u128 + n1 as u128 * t as u128) as u64, n))
+    })
+}
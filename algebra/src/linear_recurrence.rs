@@ -0,0 +1,240 @@
+//! Linear recurrences a_n = c_1 a_(n-1) + ... + c_k a_(n-k) with constant coefficients
+//!
+//! Terms are evaluated mod m either by exponentiation of the companion matrix in O(k^3 log n),
+//! or with Kitamasa's method in O(k^2 log n).
+//! Recurrences can also be recovered from the first terms of a sequence by Berlekamp–Massey.
+use crate::binary_exponentiation::{mul_mod, pow, Matrices};
+use crate::modular_inverse::modular_inverse_prime;
+
+/// Recurrence of order k given by its coefficients and first k terms
+/// ```
+/// use algebra::linear_recurrence::LinearRecurrence;
+///
+/// // Fibonacci
+/// let fib = LinearRecurrence::new(vec![1, 1], vec![0, 1]);
+/// assert_eq!(fib.prefix(10, 1000), vec![0, 1, 1, 2, 3, 5, 8, 13, 21, 34]);
+/// assert_eq!(fib.nth(100, 1000000007), 687995182);
+/// assert_eq!(fib.nth_matrix(100, 1000000007), 687995182);
+///
+/// // Tribonacci
+/// let t = LinearRecurrence::new(vec![1, 1, 1], vec![0, 0, 1]);
+/// assert_eq!(t.prefix(10, 1000), vec![0, 0, 1, 1, 2, 4, 7, 13, 24, 44]);
+/// assert_eq!(t.nth(1 << 60, 998244353), t.nth_matrix(1 << 60, 998244353));
+///
+/// // Powers of 3
+/// let g = LinearRecurrence::new(vec![3], vec![1]);
+/// assert_eq!(g.nth(30, u64::MAX), 3u64.pow(30));
+/// assert_eq!(g.nth_matrix(30, u64::MAX), 3u64.pow(30));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LinearRecurrence {
+    coefficients: Vec<u64>,
+    initial: Vec<u64>,
+}
+
+impl LinearRecurrence {
+    /// a_n = Σ coefficients[i] a_(n-1-i), starting from a_0 ... a_(k-1) = initial
+    ///
+    /// # Panics
+    /// If there aren't as many initial terms as coefficients
+    pub fn new(coefficients: Vec<u64>, initial: Vec<u64>) -> LinearRecurrence {
+        assert_eq!(coefficients.len(), initial.len(), "an order k recurrence needs k initial terms");
+
+        LinearRecurrence { coefficients, initial }
+    }
+
+    /// Order k of the recurrence
+    pub fn order(&self) -> usize {
+        self.coefficients.len()
+    }
+
+    /// c_1 ... c_k
+    pub fn coefficients(&self) -> &[u64] {
+        &self.coefficients
+    }
+
+    /// a_0 ... a_(k-1)
+    pub fn initial(&self) -> &[u64] {
+        &self.initial
+    }
+
+    /// a_0 ... a_(len-1) mod m, in O(k len)
+    pub fn prefix(&self, len: usize, m: u64) -> Vec<u64> {
+        let k = self.order();
+        let mut terms: Vec<u64> = self.initial.iter().take(len).map(|&a| a % m).collect();
+        while terms.len() < len {
+            let n = terms.len();
+            let next = (0..k).fold(0, |acc, i| {
+                add_mod(acc, mul_mod(self.coefficients[i], terms[n - 1 - i], m), m)
+            });
+            terms.push(next);
+        }
+
+        terms
+    }
+
+    /// a_n mod m with Kitamasa's method in O(k^2 log n)
+    ///
+    /// a_n = Σ r_j a_j where r(x) = x^n mod x^k - c_1 x^(k-1) - ... - c_k,
+    /// and x^n is found by binary exponentiation of polynomials reduced after every step.
+    pub fn nth(&self, n: u64, m: u64) -> u64 {
+        let k = self.order();
+        if k == 0 {
+            return 0;
+        }
+        if n < k as u64 {
+            return self.initial[n as usize] % m;
+        }
+
+        // x^n mod the characteristic polynomial, as coefficients of 1, x, ..., x^(k-1)
+        let mut result = vec![0; k];
+        result[0] = 1 % m;
+        let mut base = vec![0; k];
+        if k == 1 {
+            base[0] = self.coefficients[0] % m;
+        } else {
+            base[1] = 1 % m;
+        }
+        let mut e = n;
+        while e > 0 {
+            if e & 1 == 1 {
+                result = self.mul_reduce(&result, &base, m);
+            }
+            base = self.mul_reduce(&base, &base, m);
+            e >>= 1;
+        }
+
+        (0..k).fold(0, |acc, j| add_mod(acc, mul_mod(result[j], self.initial[j], m), m))
+    }
+
+    /// a(x) b(x) mod the characteristic polynomial, for a and b of degree below k
+    fn mul_reduce(&self, a: &[u64], b: &[u64], m: u64) -> Vec<u64> {
+        let k = self.order();
+        let mut product = vec![0; 2 * k - 1];
+        for (i, &ai) in a.iter().enumerate() {
+            if ai == 0 {
+                continue;
+            }
+            for (j, &bj) in b.iter().enumerate() {
+                product[i + j] = add_mod(product[i + j], mul_mod(ai, bj, m), m);
+            }
+        }
+        // x^d = c_1 x^(d-1) + ... + c_k x^(d-k), from the top down
+        for d in (k..product.len()).rev() {
+            let top = product[d];
+            if top == 0 {
+                continue;
+            }
+            for (i, &c) in self.coefficients.iter().enumerate() {
+                product[d - 1 - i] = add_mod(product[d - 1 - i], mul_mod(top, c, m), m);
+            }
+        }
+        product.truncate(k);
+
+        product
+    }
+
+    /// a_n mod m by raising the k x k companion matrix to the n-th power, in O(k^3 log n)
+    pub fn nth_matrix(&self, n: u64, m: u64) -> u64 {
+        let k = self.order();
+        if k == 0 {
+            return 0;
+        }
+        if n < k as u64 {
+            return self.initial[n as usize] % m;
+        }
+
+        // Maps (a_(i+k-1), ..., a_i) to (a_(i+k), ..., a_(i+1))
+        let mut companion = vec![vec![0; k]; k];
+        for (j, &c) in self.coefficients.iter().enumerate() {
+            companion[0][j] = c % m;
+        }
+        for i in 1..k {
+            companion[i][i - 1] = 1 % m;
+        }
+        let power = pow(&Matrices::new(k, m), &companion, n - (k as u64 - 1));
+
+        // The top row applied to (a_(k-1), ..., a_0)
+        (0..k).fold(0, |acc, j| {
+            add_mod(acc, mul_mod(power[0][j], self.initial[k - 1 - j], m), m)
+        })
+    }
+}
+
+/// [Berlekamp–Massey](https://en.wikipedia.org/wiki/Berlekamp%E2%80%93Massey_algorithm)
+///
+/// Coefficients c_1 ... c_k of the shortest linear recurrence generating the terms mod a prime p,
+/// in O(N^2) for N terms. 2k terms are needed for the recurrence to be pinned down.
+/// ```
+/// use algebra::linear_recurrence::berlekamp_massey;
+///
+/// assert_eq!(berlekamp_massey(&[0, 1, 1, 2, 3, 5, 8, 13], 1000000007), vec![1, 1]);
+/// assert_eq!(berlekamp_massey(&[1, 2, 4, 8, 16, 32], 1000000007), vec![2]);
+/// assert_eq!(berlekamp_massey(&[0, 0, 0, 0], 1000000007), vec![]);
+///
+/// // a_n = n^2, so a_n = 3 a_(n-1) - 3 a_(n-2) + a_(n-3)
+/// let p = 998244353;
+/// assert_eq!(berlekamp_massey(&[0, 1, 4, 9, 16, 25, 36, 49], p), vec![3, p - 3, 1]);
+/// ```
+pub fn berlekamp_massey(terms: &[u64], p: u64) -> Vec<u64> {
+    // Connection polynomials 1 - c_1 x - ... - c_L x^L, current and before the last length change
+    let mut current: Vec<u64> = vec![1 % p];
+    let mut previous: Vec<u64> = vec![1 % p];
+    let mut length = 0;
+    // Discrepancy at the last length change and the steps since
+    let mut last_discrepancy = 1;
+    let mut shift = 1;
+    for n in 0..terms.len() {
+        let discrepancy = (1..=length).fold(terms[n] % p, |acc, i| {
+            add_mod(acc, mul_mod(current[i], terms[n - i] % p, p), p)
+        });
+        if discrepancy == 0 {
+            shift += 1;
+            continue;
+        }
+
+        // current -= d / b x^shift previous
+        let scale = mul_mod(discrepancy, modular_inverse_prime(last_discrepancy, p).unwrap(), p);
+        let before = current.clone();
+        if current.len() < previous.len() + shift {
+            current.resize(previous.len() + shift, 0);
+        }
+        for (i, &c) in previous.iter().enumerate() {
+            current[i + shift] = add_mod(current[i + shift], p - mul_mod(scale, c, p), p);
+        }
+        if 2 * length <= n {
+            length = n + 1 - length;
+            previous = before;
+            last_discrepancy = discrepancy;
+            shift = 1;
+        } else {
+            shift += 1;
+        }
+    }
+    current.resize(length + 1, 0);
+
+    current[1..].iter().map(|&c| (p - c) % p).collect()
+}
+
+/// n-th term mod a prime p of the sequence starting with the given terms
+///
+/// The recurrence found by [`berlekamp_massey`] is evaluated with [`LinearRecurrence::nth`].
+/// Only right when the terms are enough to determine the sequence, twice its order or more.
+/// ```
+/// use algebra::linear_recurrence::guess_nth_term;
+///
+/// let p = 1000000007;
+/// assert_eq!(guess_nth_term(&[0, 1, 1, 2, 3, 5], 100, p), 687995182);
+/// assert_eq!(guess_nth_term(&[0, 1, 4, 9, 16, 25, 36], 1000000, p), 999993007);
+/// assert_eq!(guess_nth_term(&[5, 5, 5], 1 << 62, p), 5);
+/// ```
+pub fn guess_nth_term(terms: &[u64], n: u64, p: u64) -> u64 {
+    let coefficients = berlekamp_massey(terms, p);
+    let initial = terms[..coefficients.len()].iter().map(|&a| a % p).collect();
+
+    LinearRecurrence::new(coefficients, initial).nth(n, p)
+}
+
+fn add_mod(a: u64, b: u64, m: u64) -> u64 {
+    ((a as u128 + b as u128) % m as u128) as u64
+}
@@ -1,5 +1,6 @@
 //! [Modular Inverse](https://cp-algorithms.com/algebra/module-inverse.html)
-use crate::gcd::gcd_extended1;
+use crate::binary_exponentiation::{binary_exponentiation_mod, mul_mod};
+use crate::gcd::{gcd, gcd_extended1};
 
 /// Modular Inverse Using Extended Euclidean Algorithm
 ///
@@ -15,3 +16,101 @@ pub fn modular_inverse_extended_gcd(a: i64, m: i64) -> Option<i64> {
         _ => None
     }
 }
+
+/// Modular Inverse Using Fermat's Little Theorem
+///
+/// a^-1 = a^(p - 2) mod a prime p, None if p divides a
+/// ```
+/// assert_eq!(algebra::modular_inverse::modular_inverse_prime(2, 5), Some(3));
+/// assert_eq!(algebra::modular_inverse::modular_inverse_prime(10, 5), None);
+/// assert_eq!(algebra::modular_inverse::modular_inverse_prime(2, 18446744073709551557), Some(9223372036854775779));
+/// ```
+pub fn modular_inverse_prime(a: u64, p: u64) -> Option<u64> {
+    if a.is_multiple_of(p) {
+        return None;
+    }
+
+    Some(binary_exponentiation_mod(a, p - 2, p))
+}
+
+/// Inverts every value mod m with Montgomery's trick
+///
+/// Prefix products are inverted once and unwound from the back, so n values take
+/// a single inversion and 3n multiplications. m needn't be prime.
+/// Returns the index of the first value not coprime to m if there is one.
+/// ```
+/// use algebra::modular_inverse::batch_inverse;
+///
+/// assert_eq!(batch_inverse(&[1, 2, 3, 4], 5), Ok(vec![1, 3, 2, 4]));
+/// assert_eq!(batch_inverse(&[7, 11, 13], 36), Ok(vec![31, 23, 25]));
+/// assert_eq!(batch_inverse(&[1, 2, 4, 6], 9), Err(3));
+/// assert_eq!(batch_inverse(&[], 7), Ok(vec![]));
+/// ```
+pub fn batch_inverse(values: &[u64], m: u64) -> Result<Vec<u64>, usize> {
+    let mut prefix = Vec::with_capacity(values.len());
+    let mut product = 1 % m;
+    for &v in values.iter() {
+        product = mul_mod(product, v, m);
+        prefix.push(product);
+    }
+
+    let mut inv = match modular_inverse_u64(product, m) {
+        Some(inv) => inv,
+        None => return Err(values.iter().position(|&v| gcd(v % m, m) != 1).unwrap()),
+    };
+    let mut inverses = vec![0; values.len()];
+    for i in (0..values.len()).rev() {
+        // inv is (v_0 ... v_i)^-1
+        inverses[i] = if i == 0 { inv } else { mul_mod(inv, prefix[i - 1], m) };
+        inv = mul_mod(inv, values[i], m);
+    }
+
+    Ok(inverses)
+}
+
+/// Inverses of every i in [1, n] mod p in O(n)
+///
+/// inv[i] = -(p / i) inv[p mod i], from p = (p / i) i + p mod i.
+/// The entry at 0 is unused and set to zero.
+/// p is usually prime, otherwise the smallest i not coprime to it is returned as the error.
+/// ```
+/// use algebra::modular_inverse::inverses_up_to;
+///
+/// assert_eq!(inverses_up_to(6, 7), Ok(vec![0, 1, 4, 5, 2, 3, 6]));
+/// assert_eq!(inverses_up_to(7, 7), Err(7));
+/// assert_eq!(inverses_up_to(4, 10), Err(2));
+///
+/// let p = 1000000007;
+/// let inv = inverses_up_to(100000, p).unwrap();
+/// assert!((1..=100000).all(|i| i * inv[i as usize] % p == 1));
+/// ```
+pub fn inverses_up_to(n: u64, p: u64) -> Result<Vec<u64>, u64> {
+    let mut inv = vec![0; n as usize + 1];
+    if n >= 1 {
+        inv[1] = 1 % p;
+    }
+    for i in 2..=n {
+        if p.is_multiple_of(i) {
+            return Err(i);
+        }
+        inv[i as usize] = mul_mod(p - p / i, inv[(p % i) as usize], p);
+    }
+
+    Ok(inv)
+}
+
+/// a^-1 mod m for any 64 bit modulus, None if a isn't coprime to m
+pub(crate) fn modular_inverse_u64(a: u64, m: u64) -> Option<u64> {
+    let (mut r0, mut r1) = (m as i128, (a % m) as i128);
+    let (mut t0, mut t1) = (0i128, 1i128);
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (t0, t1) = (t1, t0 - q * t1);
+    }
+
+    match r0 {
+        1 => Some(t0.rem_euclid(m as i128) as u64),
+        _ => None,
+    }
+}
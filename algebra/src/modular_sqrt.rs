@@ -0,0 +1,67 @@
+//! [Modular Square Root](https://en.wikipedia.org/wiki/Tonelli%E2%80%93Shanks_algorithm)
+use crate::binary_exponentiation::{binary_exponentiation_mod, mul_mod};
+
+/// Legendre symbol (a / p) for an odd prime p
+///
+/// Returns 1 if a is a non-zero quadratic residue mod p, -1 if it's a non-residue and 0 if p divides a
+/// ```
+/// assert_eq!(algebra::modular_sqrt::legendre(2, 7), 1);
+/// assert_eq!(algebra::modular_sqrt::legendre(3, 7), -1);
+/// assert_eq!(algebra::modular_sqrt::legendre(14, 7), 0);
+/// ```
+pub fn legendre(a: u64, p: u64) -> i32 {
+    match binary_exponentiation_mod(a % p, (p - 1) / 2, p) {
+        0 => 0,
+        1 => 1,
+        _ => -1,
+    }
+}
+
+/// Tonelli Shanks
+///
+/// Finds x with x^2 = a (mod p) for a prime p in O(log^2 p).
+/// Returns the smaller of the two roots x and p - x, or None if a is a non-residue.
+/// ```
+/// assert_eq!(algebra::modular_sqrt::sqrt_mod(2, 7), Some(3));
+/// assert_eq!(algebra::modular_sqrt::sqrt_mod(3, 7), None);
+/// assert_eq!(algebra::modular_sqrt::sqrt_mod(5, 1000000009), Some(383008016));
+/// ```
+pub fn sqrt_mod(a: u64, p: u64) -> Option<u64> {
+    let a = a % p;
+    if p == 2 || a == 0 {
+        return Some(a);
+    }
+    if legendre(a, p) != 1 {
+        return None;
+    }
+
+    // p - 1 = q * 2^s with q odd
+    let mut q = p - 1;
+    let mut s = 0;
+    while q.is_multiple_of(2) {
+        q /= 2;
+        s += 1;
+    }
+    let z = (2..p).find(|&z| legendre(z, p) == -1).unwrap();
+
+    let mut m = s;
+    let mut c = binary_exponentiation_mod(z, q, p);
+    let mut t = binary_exponentiation_mod(a, q, p);
+    let mut r = binary_exponentiation_mod(a, q.div_ceil(2), p);
+    while t != 1 {
+        // Least i with t^(2^i) = 1
+        let mut i = 0;
+        let mut t2 = t;
+        while t2 != 1 {
+            t2 = mul_mod(t2, t2, p);
+            i += 1;
+        }
+        let b = binary_exponentiation_mod(c, 1 << (m - i - 1), p);
+        m = i;
+        c = mul_mod(b, b, p);
+        t = mul_mod(t, c, p);
+        r = mul_mod(r, b, p);
+    }
+
+    Some(if r < p - r { r } else { p - r })
+}
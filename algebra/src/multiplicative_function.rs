@@ -0,0 +1,109 @@
+//! [Multiplicative Functions](https://cp-algorithms.com/algebra/prime-sieve-linear.html)
+use num::{One, Zero};
+use std::ops::Mul;
+
+use crate::prime::SpfSieve;
+
+/// Tabulates a multiplicative function over [0, n] with the linear sieve in O(n)
+///
+/// Every i is split by its least prime factor from [`SpfSieve`] into p^k and a coprime rest.
+///
+/// f is given by its values on prime powers, `f_prime_power(p, k) = f(p^k)`.
+/// Every other value follows from f(ab) = f(a)f(b) for coprime a and b.
+/// The entry at 0 is unused and set to zero.
+/// ```
+/// // f(n) = n
+/// let id = algebra::multiplicative_function::multiplicative_sieve(10, |p, k| p.pow(k) as u64);
+/// assert_eq!(id, vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+///
+/// // Number of square divisors
+/// let f = algebra::multiplicative_function::multiplicative_sieve(12, |_, k| k as u64 / 2 + 1);
+/// assert_eq!(f[12], 2);
+/// ```
+pub fn multiplicative_sieve<T, F>(n: usize, f_prime_power: F) -> Vec<T>
+where
+    T: Copy + Zero + One + Mul<Output = T>,
+    F: Fn(usize, u32) -> T,
+{
+    let sieve = SpfSieve::new(n);
+    let mut f: Vec<T> = vec![T::zero(); n + 1];
+
+    if n >= 1 {
+        f[1] = T::one();
+    }
+    for i in 2..(n+1) {
+        // i = p^k * rest with p the least prime factor, p^k is found in O(1) on average
+        let p = sieve.smallest_prime_factor(i);
+        let mut rest = i;
+        let mut pk = 1;
+        let mut k = 0;
+        while rest % p == 0 {
+            rest /= p;
+            pk *= p;
+            k += 1;
+        }
+        f[i] = if rest == 1 {
+            f_prime_power(p, k)
+        } else {
+            f[rest] * f[pk]
+        };
+    }
+
+    f
+}
+
+/// Euler's totient φ(i) for every i in [0, n]
+/// ```
+/// let phi = algebra::multiplicative_function::phi_table(10);
+/// assert_eq!(phi, vec![0, 1, 1, 2, 2, 4, 2, 6, 4, 6, 4]);
+/// ```
+pub fn phi_table(n: usize) -> Vec<u64> {
+    multiplicative_sieve(n, phi_prime_power)
+}
+
+/// Möbius function μ(i) for every i in [0, n]
+/// ```
+/// let mu = algebra::multiplicative_function::mobius_table(10);
+/// assert_eq!(mu, vec![0, 1, -1, -1, 0, -1, 1, -1, 0, 0, 1]);
+/// ```
+pub fn mobius_table(n: usize) -> Vec<i8> {
+    multiplicative_sieve(n, mobius_prime_power)
+}
+
+/// Number of divisors d(i) for every i in [0, n]
+/// ```
+/// let d = algebra::multiplicative_function::divisor_count_table(12);
+/// assert_eq!(d, vec![0, 1, 2, 2, 3, 2, 4, 2, 4, 3, 4, 2, 6]);
+/// ```
+pub fn divisor_count_table(n: usize) -> Vec<u64> {
+    multiplicative_sieve(n, divisor_count_prime_power)
+}
+
+/// Sum of divisors σ(i) for every i in [0, n]
+/// ```
+/// let sigma = algebra::multiplicative_function::divisor_sum_table(12);
+/// assert_eq!(sigma, vec![0, 1, 3, 4, 7, 6, 12, 8, 15, 13, 18, 12, 28]);
+/// ```
+pub fn divisor_sum_table(n: usize) -> Vec<u64> {
+    multiplicative_sieve(n, divisor_sum_prime_power)
+}
+
+/// φ(p^k)
+pub(crate) fn phi_prime_power(p: usize, k: u32) -> u64 {
+    ((p - 1) * p.pow(k - 1)) as u64
+}
+
+/// μ(p^k)
+pub(crate) fn mobius_prime_power(_: usize, k: u32) -> i8 {
+    if k == 1 { -1 } else { 0 }
+}
+
+/// d(p^k)
+pub(crate) fn divisor_count_prime_power(_: usize, k: u32) -> u64 {
+    k as u64 + 1
+}
+
+/// σ(p^k)
+pub(crate) fn divisor_sum_prime_power(p: usize, k: u32) -> u64 {
+    ((p.pow(k + 1) - 1) / (p - 1)) as u64
+}
@@ -0,0 +1,147 @@
+//! Parallel sieving with [rayon](https://docs.rs/rayon), enabled by the `parallel` feature
+//!
+//! Every function splits its range into independent segments sieved on separate threads
+//! and returns exactly what its serial counterpart does.
+use num::{One, Zero};
+use rayon::prelude::*;
+use std::cmp;
+use std::ops::Mul;
+
+use crate::multiplicative_function::{
+    divisor_count_prime_power, divisor_sum_prime_power, mobius_prime_power, phi_prime_power,
+};
+use crate::prime::{primes, sieve_segment, SEGMENT_SIZE};
+use crate::roots::isqrt;
+
+/// Parallel [`count_primes`](crate::prime::count_primes)
+/// ```
+/// let n = 3000000;
+/// assert_eq!(algebra::parallel::count_primes(n), algebra::prime::count_primes(n));
+/// assert_eq!(algebra::parallel::count_primes(1), algebra::prime::count_primes(1));
+/// ```
+pub fn count_primes(n: usize) -> usize {
+    let base_primes = primes(isqrt(n as u64) as usize);
+    (0..n / SEGMENT_SIZE + 1)
+        .into_par_iter()
+        .map(|k| {
+            let lo = k * SEGMENT_SIZE;
+            let hi = cmp::min(lo + SEGMENT_SIZE, n + 1);
+            sieve_segment(lo, hi, &base_primes).into_iter().filter(|&p| p).count()
+        })
+        .sum()
+}
+
+/// Parallel [`primes_in_range`](crate::prime::primes_in_range)
+/// ```
+/// let (l, r) = (1000000, 2000000);
+/// assert_eq!(algebra::parallel::primes_in_range(l, r), algebra::prime::primes_in_range(l, r));
+/// assert_eq!(algebra::parallel::primes_in_range(0, 30), algebra::prime::primes_in_range(0, 30));
+/// ```
+pub fn primes_in_range(l: usize, r: usize) -> Vec<usize> {
+    if l > r {
+        return Vec::new();
+    }
+    let base_primes = primes(isqrt(r as u64) as usize);
+    let segments: Vec<Vec<usize>> = (0..(r - l) / SEGMENT_SIZE + 1)
+        .into_par_iter()
+        .map(|k| {
+            let lo = l + k * SEGMENT_SIZE;
+            let hi = cmp::min(lo + SEGMENT_SIZE, r + 1);
+            let segment = sieve_segment(lo, hi, &base_primes);
+            (lo..hi).filter(|&i| segment[i - lo]).collect()
+        })
+        .collect();
+
+    segments.concat()
+}
+
+/// Parallel [`multiplicative_sieve`](crate::multiplicative_function::multiplicative_sieve)
+///
+/// Each segment divides its numbers by the primes up to sqrt(n),
+/// so whatever is left above 1 is the single prime factor greater than sqrt(n)
+/// ```
+/// let n = 100000;
+/// let f = |p: usize, k: u32| (p as u64 + k as u64) % 1000;
+/// assert_eq!(
+///     algebra::parallel::multiplicative_sieve(n, f),
+///     algebra::multiplicative_function::multiplicative_sieve(n, f)
+/// );
+/// ```
+pub fn multiplicative_sieve<T, F>(n: usize, f_prime_power: F) -> Vec<T>
+where
+    T: Copy + Zero + One + Mul<Output = T> + Send + Sync,
+    F: Fn(usize, u32) -> T + Sync,
+{
+    let base_primes = primes(isqrt(n as u64) as usize);
+    let mut f: Vec<T> = vec![T::zero(); n + 1];
+    f.par_chunks_mut(SEGMENT_SIZE).enumerate().for_each(|(k, values)| {
+        let lo = k * SEGMENT_SIZE;
+        let mut rest: Vec<usize> = (lo..lo + values.len()).collect();
+        for v in values.iter_mut() {
+            *v = T::one();
+        }
+        for &p in base_primes.iter() {
+            let start = lo.div_ceil(p) * p;
+            for j in (cmp::max(start, p)..lo + values.len()).step_by(p) {
+                let mut e = 0;
+                while rest[j - lo].is_multiple_of(p) {
+                    rest[j - lo] /= p;
+                    e += 1;
+                }
+                values[j - lo] = values[j - lo] * f_prime_power(p, e);
+            }
+        }
+        for (v, &r) in values.iter_mut().zip(rest.iter()) {
+            if r > 1 {
+                *v = *v * f_prime_power(r, 1);
+            }
+        }
+        if lo == 0 {
+            values[0] = T::zero();
+        }
+    });
+
+    f
+}
+
+/// Parallel [`phi_table`](crate::multiplicative_function::phi_table)
+/// ```
+/// let n = 100000;
+/// assert_eq!(algebra::parallel::phi_table(n), algebra::multiplicative_function::phi_table(n));
+/// ```
+pub fn phi_table(n: usize) -> Vec<u64> {
+    multiplicative_sieve(n, phi_prime_power)
+}
+
+/// Parallel [`mobius_table`](crate::multiplicative_function::mobius_table)
+/// ```
+/// let n = 100000;
+/// assert_eq!(algebra::parallel::mobius_table(n), algebra::multiplicative_function::mobius_table(n));
+/// ```
+pub fn mobius_table(n: usize) -> Vec<i8> {
+    multiplicative_sieve(n, mobius_prime_power)
+}
+
+/// Parallel [`divisor_count_table`](crate::multiplicative_function::divisor_count_table)
+/// ```
+/// let n = 100000;
+/// assert_eq!(
+///     algebra::parallel::divisor_count_table(n),
+///     algebra::multiplicative_function::divisor_count_table(n)
+/// );
+/// ```
+pub fn divisor_count_table(n: usize) -> Vec<u64> {
+    multiplicative_sieve(n, divisor_count_prime_power)
+}
+
+/// Parallel [`divisor_sum_table`](crate::multiplicative_function::divisor_sum_table)
+/// ```
+/// let n = 100000;
+/// assert_eq!(
+///     algebra::parallel::divisor_sum_table(n),
+///     algebra::multiplicative_function::divisor_sum_table(n)
+/// );
+/// ```
+pub fn divisor_sum_table(n: usize) -> Vec<u64> {
+    multiplicative_sieve(n, divisor_sum_prime_power)
+}
@@ -1,8 +1,9 @@
 //! [Primality Tests](https://cp-algorithms.com/algebra/primality_tests.html)
 
+use num::{BigInt, Integer, One};
 use rand::{thread_rng, RngCore};
 
-use crate::binary_exponentiation::binary_exponentiation_mod;
+use crate::binary_exponentiation::{binary_exponentiation_mod, mul_mod};
 
 /// Trial Division
 /// ```
@@ -48,7 +49,7 @@ fn check_composite(n: u64, a: u64, d: u64, s: i32) -> bool {
     }
     if s > 1 {
         for _ in 1..s {
-            x = (((x * x) as u128) % n as u128) as u64;
+            x = mul_mod(x, x, n);
             if x == n -1 {
                 return false;
             }
@@ -112,3 +113,57 @@ pub fn deterministic_millerrabin(n: u64) -> bool {
     }
     true
 }
+
+/// Miller Rabin Primality Test for big integers
+///
+/// Uses the prime bases of [`deterministic_millerrabin`] and 41, the first 13 primes.
+/// These are deterministic below 3.3 * 10^24, the first 12 alone only below 3.18 * 10^23.
+/// Above that it is a strong probable prime test.
+/// ```
+/// use num::BigInt;
+///
+/// let p: BigInt = "170141183460469231731687303715884105727".parse().unwrap(); // 2^127 - 1
+/// assert_eq!(algebra::primality::millerrabin_bigint(&p), true);
+/// assert_eq!(algebra::primality::millerrabin_bigint(&(&p * &p)), false);
+///
+/// // Strong pseudoprime to the bases 2 to 37, caught by 41
+/// let n: BigInt = "318665857834031151167461".parse().unwrap();
+/// assert_eq!(algebra::primality::millerrabin_bigint(&n), false);
+/// ```
+pub fn millerrabin_bigint(n: &BigInt) -> bool {
+    let two = BigInt::from(2);
+    if n < &BigInt::from(4) {
+        return n == &two || n == &BigInt::from(3);
+    }
+
+    let n_1: BigInt = n - 1;
+    let mut s = 0;
+    let mut d = n_1.clone();
+    while d.is_even() {
+        s += 1;
+        d >>= 1;
+    }
+
+    for &a in [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41].iter() {
+        let a = BigInt::from(a);
+        if n == &a {
+            return true;
+        }
+        let mut x = a.modpow(&d, n);
+        if x.is_one() || x == n_1 {
+            continue;
+        }
+        let mut composite = true;
+        for _ in 1..s {
+            x = &x * &x % n;
+            if x == n_1 {
+                composite = false;
+                break;
+            }
+        }
+        if composite {
+            return false;
+        }
+    }
+    true
+}
@@ -2,17 +2,21 @@
 //! [Linear Sieve](https://cp-algorithms.com/algebra/prime-sieve-linear.html)
 
 use std::cmp;
+use std::iter;
+
+use crate::roots::isqrt;
 
 /// Block Sieving
 /// Returns count of retime numbers less than n
 /// ```
 /// assert_eq!(algebra::prime::count_primes(100), 25);
 /// assert_eq!(algebra::prime::count_primes(1000000), 78498);
+/// assert_eq!(algebra::prime::count_primes(100100000), 5766866);
 /// ```
 pub fn count_primes(n: usize) -> usize {
     let block_size = 10000;
     let mut retimes: Vec<usize> = Vec::new();
-    let sqrt_n = (n as f64).sqrt() as usize + 1;
+    let sqrt_n = isqrt(n as u64) as usize + 1;
     let mut is_retime: Vec<u8> = vec![1; sqrt_n];
 
     for i in 2..sqrt_n {
@@ -26,7 +30,7 @@ pub fn count_primes(n: usize) -> usize {
 
     let mut count = 0;
     let mut block = vec![0; block_size];
-    for k in 0..block_size {
+    for k in 0..(n / block_size + 1) {
         let start = k * block_size;
         if n < start {
             break
@@ -57,6 +61,52 @@ pub fn count_primes(n: usize) -> usize {
     count
 }
 
+/// Block size used by segmented sieves
+pub(crate) const SEGMENT_SIZE: usize = 1 << 15;
+
+/// Sieves the segment [lo, hi) with the given base primes
+///
+/// The base primes must include every prime up to sqrt(hi - 1).
+/// Returns whether each number in the segment is prime.
+pub(crate) fn sieve_segment(lo: usize, hi: usize, base_primes: &[usize]) -> Vec<bool> {
+    let mut is_prime = vec![true; hi - lo];
+    for &p in base_primes {
+        if p * p >= hi {
+            break;
+        }
+        let start = cmp::max(p * p, lo.div_ceil(p) * p);
+        for j in (start..hi).step_by(p) {
+            is_prime[j - lo] = false;
+        }
+    }
+    for i in lo..cmp::min(2, hi) {
+        is_prime[i - lo] = false;
+    }
+
+    is_prime
+}
+
+/// Segmented Sieve
+/// Returns primes in [l, r] in O((r - l) log log r + sqrt(r)) with O(sqrt(r) + block size) memory
+/// ```
+/// assert_eq!(algebra::prime::primes_in_range(90, 110), vec![97, 101, 103, 107, 109]);
+/// assert_eq!(algebra::prime::primes_in_range(1000000000, 1000000100).len(), 7);
+/// ```
+pub fn primes_in_range(l: usize, r: usize) -> Vec<usize> {
+    if l > r {
+        return Vec::new();
+    }
+    let base_primes = primes(isqrt(r as u64) as usize);
+    let mut ret = Vec::new();
+    for lo in (l..=r).step_by(SEGMENT_SIZE) {
+        let hi = cmp::min(lo + SEGMENT_SIZE, r + 1);
+        let segment = sieve_segment(lo, hi, &base_primes);
+        ret.extend((lo..hi).filter(|&i| segment[i - lo]));
+    }
+
+    ret
+}
+
 /// Finds primes less than n in O(n)
 /// Returns vector of prime numbers
 /// ```
@@ -66,8 +116,14 @@ pub fn count_primes(n: usize) -> usize {
 /// ```
 /// [Phi function values](https://primes.utm.edu/howmany.html)
 pub fn primes(n: usize) -> Vec<usize> {
+    linear_sieve(n).1
+}
+
+/// Linear sieve returning the least prime factor of every number in [0, n]
+/// along with the primes in [0, n]
+fn linear_sieve(n: usize) -> (Vec<usize>, Vec<usize>) {
     let mut lp: Vec<usize> = vec![0; n + 1];
-    let mut ret: Vec<usize>= Vec::new();
+    let mut ret: Vec<usize> = Vec::new();
 
     for i in 2..(n+1) {
         if lp[i] == 0 {
@@ -75,13 +131,275 @@ pub fn primes(n: usize) -> Vec<usize> {
             ret.push(i);
         }
 
-        for j in 0..ret.len() {
-            if ret[j] > lp[i] || i * ret[j] > n {
+        for &p in ret.iter() {
+            if p > lp[i] || i * p > n {
                 break
             }
-            lp[i * ret[j]] = ret[j];
+            lp[i * p] = p;
         }
     }
 
-    ret
+    (lp, ret)
+}
+
+/// Smallest prime factor table
+///
+/// Keeps the least prime factor of every number up to n from the linear sieve,
+/// answering primality in O(1) and factorization in O(log x)
+/// ```
+/// let sieve = algebra::prime::SpfSieve::new(1000);
+/// assert_eq!(sieve.is_prime(997), true);
+/// assert_eq!(sieve.is_prime(999), false);
+/// assert_eq!(sieve.factorize(360), vec![2, 2, 2, 3, 3, 5]);
+/// assert_eq!(sieve.divisors(12), vec![1, 2, 3, 4, 6, 12]);
+/// assert_eq!(sieve.primes().len(), 168);
+/// ```
+pub struct SpfSieve {
+    lp: Vec<usize>,
+    primes: Vec<usize>,
+}
+
+impl SpfSieve {
+    /// Sieves all numbers in [0, n]
+    pub fn new(n: usize) -> SpfSieve {
+        let (lp, primes) = linear_sieve(n);
+        SpfSieve { lp, primes }
+    }
+
+    /// Largest number covered by the sieve
+    pub fn limit(&self) -> usize {
+        self.lp.len() - 1
+    }
+
+    /// Least prime factor of x, 0 for x < 2
+    pub fn smallest_prime_factor(&self, x: usize) -> usize {
+        self.lp[x]
+    }
+
+    /// Checks if x is prime in O(1)
+    pub fn is_prime(&self, x: usize) -> bool {
+        x >= 2 && self.lp[x] == x
+    }
+
+    /// Primes in [0, n] in increasing order
+    pub fn primes(&self) -> &[usize] {
+        &self.primes
+    }
+
+    /// Prime factors of x in increasing order, with multiplicity, in O(log x)
+    pub fn factorize(&self, mut x: usize) -> Vec<usize> {
+        let mut factorization = Vec::new();
+        while x > 1 {
+            let p = self.lp[x];
+            factorization.push(p);
+            x /= p;
+        }
+
+        factorization
+    }
+
+    /// Divisors of x in increasing order
+    pub fn divisors(&self, x: usize) -> Vec<usize> {
+        let mut divisors = vec![1];
+        let factorization = self.factorize(x);
+        let mut i = 0;
+        while i < factorization.len() {
+            let p = factorization[i];
+            let count = divisors.len();
+            let mut pk = 1;
+            while i < factorization.len() && factorization[i] == p {
+                pk *= p;
+                for j in 0..count {
+                    divisors.push(divisors[j] * pk);
+                }
+                i += 1;
+            }
+        }
+        divisors.sort_unstable();
+
+        divisors
+    }
+}
+
+/// Counts primes less than or equal to n in O(n^(3/4))
+///
+/// [Lucy_Hedgehog's method](https://projecteuler.net/thread=10;page=5#111677)
+/// keeps S(v), the count of numbers in [2, v] surviving the sieve so far,
+/// only for the O(sqrt(n)) distinct values v = n / i.
+/// Sieving by p removes S(v / p) - S(p - 1) numbers with least prime factor p.
+/// ```
+/// assert_eq!(algebra::prime::prime_pi(100), 25);
+/// assert_eq!(algebra::prime::prime_pi(1000000), algebra::prime::count_primes(1000000) as u64);
+/// assert_eq!(algebra::prime::prime_pi(10000000000), 455052511);
+/// ```
+pub fn prime_pi(n: u64) -> u64 {
+    if n < 2 {
+        return 0;
+    }
+    let r = isqrt(n);
+    // small[v] = S(v) for v <= r, large[i] = S(n / i) for i <= r
+    let mut small: Vec<u64> = (0..=r).map(|v| v.saturating_sub(1)).collect();
+    let mut large: Vec<u64> = iter::once(0).chain((1..=r).map(|i| n / i - 1)).collect();
+
+    for p in 2..=r {
+        if small[p as usize] == small[p as usize - 1] {
+            continue;
+        }
+        let sp = small[p as usize - 1];
+        let p2 = p * p;
+        for i in 1..=cmp::min(r, n / p2) {
+            let d = i * p;
+            let s = if d <= r { large[d as usize] } else { small[(n / d) as usize] };
+            large[i as usize] -= s - sp;
+        }
+        for v in (p2..=r).rev() {
+            small[v as usize] -= small[(v / p) as usize] - sp;
+        }
+    }
+
+    large[1]
+}
+
+/// Sum of primes less than or equal to n modulus m in O(n^(3/4))
+///
+/// Same sieve as [`prime_pi`] with S(v) holding the sum instead of the count,
+/// so sieving by p removes p * (S(v / p) - S(p - 1))
+/// ```
+/// assert_eq!(algebra::prime::prime_sum(10, 1000), 17);
+/// assert_eq!(algebra::prime::prime_sum(2000000, u64::max_value()), 142913828922);
+/// assert_eq!(algebra::prime::prime_sum(10000000000, 1000000007), 35972319);
+/// ```
+pub fn prime_sum(n: u64, m: u64) -> u64 {
+    if n < 2 {
+        return 0;
+    }
+    let m128 = m as u128;
+    let sum_to = |v: u64| -> u64 {
+        let v = v as u128;
+        ((v * (v + 1) / 2 - 1) % m128) as u64
+    };
+    let sub = |a: u64, b: u64| if a >= b { a - b } else { a + (m - b) };
+    let r = isqrt(n);
+    let mut small: Vec<u64> = iter::once(0).chain((1..=r).map(sum_to)).collect();
+    let mut large: Vec<u64> = iter::once(0).chain((1..=r).map(|i| sum_to(n / i))).collect();
+
+    for p in 2..=r {
+        if small[p as usize] == small[p as usize - 1] {
+            continue;
+        }
+        let sp = small[p as usize - 1];
+        let p2 = p * p;
+        let pm = (p % m) as u128;
+        for i in 1..=cmp::min(r, n / p2) {
+            let d = i * p;
+            let s = if d <= r { large[d as usize] } else { small[(n / d) as usize] };
+            let removed = (pm * sub(s, sp) as u128 % m128) as u64;
+            large[i as usize] = sub(large[i as usize], removed);
+        }
+        for v in (p2..=r).rev() {
+            let removed = (pm * sub(small[(v / p) as usize], sp) as u128 % m128) as u64;
+            small[v as usize] = sub(small[v as usize], removed);
+        }
+    }
+
+    large[1]
+}
+
+/// Bit of the byte holding numbers coprime to 30, indexed by `n % 30`
+/// (numbers sharing a factor with 30 map to 0)
+const WHEEL_BIT: [u8; 30] = [
+    0, 1, 0, 0, 0, 0, 0, 2, 0, 0, 0, 4, 0, 8, 0, 0, 0, 16, 0, 32, 0, 0, 0, 64, 0, 0, 0, 0, 0, 128,
+];
+
+/// Residues modulo 30 coprime to 30, one per bit of a byte
+const WHEEL_RESIDUES: [usize; 8] = [1, 7, 11, 13, 17, 19, 23, 29];
+
+/// Bit-packed Sieve of Eratosthenes over the mod 30 wheel
+///
+/// Only numbers coprime to 30 are stored, 8 of every 30 in a single byte,
+/// so sieving up to 1e9 takes about 33MB instead of one byte (or word) per number.
+/// ```
+/// let sieve = algebra::prime::BitSieve::new(1000000);
+/// assert_eq!(sieve.count(), 78498);
+/// assert_eq!(sieve.is_prime(999983), true);
+/// assert_eq!(sieve.is_prime(999985), false);
+/// assert_eq!(sieve.iter().take(5).collect::<Vec<_>>(), vec![2, 3, 5, 7, 11]);
+/// assert_eq!(sieve.iter().count(), algebra::prime::primes(1000000).len());
+/// ```
+pub struct BitSieve {
+    n: usize,
+    bits: Vec<u8>,
+}
+
+impl BitSieve {
+    /// Sieves all numbers in [0, n]
+    pub fn new(n: usize) -> BitSieve {
+        let mut bits = vec![0xff; n / 30 + 1];
+        // 1 is not a prime
+        bits[0] &= !WHEEL_BIT[1];
+
+        let mut p = 7;
+        while p * p <= n {
+            if bits[p / 30] & WHEEL_BIT[p % 30] != 0 {
+                // Multiples p * q with q coprime to 30 and q ≡ r (mod 30) are 30 * p apart,
+                // so they share a bit and sit p bytes apart
+                for &r in WHEEL_RESIDUES.iter() {
+                    let q = p + (r + 30 - p % 30) % 30;
+                    let bit = !WHEEL_BIT[p * q % 30];
+                    let mut i = p * q / 30;
+                    while i < bits.len() {
+                        bits[i] &= bit;
+                        i += p;
+                    }
+                }
+            }
+            p += 1;
+        }
+
+        // Clear everything above n in the last byte
+        let last = bits.len() - 1;
+        for &r in WHEEL_RESIDUES.iter() {
+            if last * 30 + r > n {
+                bits[last] &= !WHEEL_BIT[r];
+            }
+        }
+
+        BitSieve { n, bits }
+    }
+
+    /// Largest number covered by the sieve
+    pub fn limit(&self) -> usize {
+        self.n
+    }
+
+    /// Checks if i is prime in O(1)
+    ///
+    /// Panics if i is above the sieve limit
+    pub fn is_prime(&self, i: usize) -> bool {
+        assert!(i <= self.n, "{} is above the sieve limit {}", i, self.n);
+        match i {
+            2 | 3 | 5 => true,
+            _ => self.bits[i / 30] & WHEEL_BIT[i % 30] != 0,
+        }
+    }
+
+    /// Number of primes in [0, n]
+    pub fn count(&self) -> usize {
+        let small = [2, 3, 5].iter().filter(|&&p| p <= self.n).count();
+        small + self.bits.iter().map(|b| b.count_ones() as usize).sum::<usize>()
+    }
+
+    /// Primes in [0, n] in increasing order
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        let n = self.n;
+        let small = [2, 3, 5].iter().cloned().filter(move |&p| p <= n);
+        let rest = self.bits.iter().enumerate().flat_map(|(k, &b)| {
+            WHEEL_RESIDUES
+                .iter()
+                .enumerate()
+                .filter(move |&(j, _)| b & (1 << j) != 0)
+                .map(move |(_, &r)| k * 30 + r)
+        });
+        small.chain(rest)
+    }
 }
@@ -0,0 +1,121 @@
+//! [Primitive Roots](https://cp-algorithms.com/algebra/primitive-root.html) and
+//! [Discrete Roots](https://cp-algorithms.com/algebra/discrete-root.html)
+use crate::binary_exponentiation::{binary_exponentiation_mod, mul_mod};
+use crate::discrete_log::pohlig_hellman;
+use crate::factorizer::{AutoFactorizer, Factorizer};
+use crate::gcd::gcd;
+use crate::modular_inverse::modular_inverse_u64;
+use crate::totient::euler_phi;
+
+/// Whether g generates the multiplicative group mod n
+///
+/// g must be coprime to n and have order φ(n); g = 0 counts as a generator mod 1.
+/// ```
+/// use algebra::primitive_root::is_primitive_root;
+///
+/// assert!(is_primitive_root(3, 7));
+/// assert!(!is_primitive_root(2, 7));
+/// assert!(is_primitive_root(5, 18));
+/// assert!(!is_primitive_root(3, 8));
+/// ```
+pub fn is_primitive_root(g: u64, n: u64) -> bool {
+    if n == 0 || gcd(g % n, n) != 1 {
+        return false;
+    }
+    let phi = euler_phi(n);
+
+    is_generator(g, n, phi, &phi_prime_factors(phi))
+}
+
+/// g^(φ/p) != 1 for every prime p dividing φ
+fn is_generator(g: u64, n: u64, phi: u64, primes: &[u64]) -> bool {
+    primes.iter().all(|&p| binary_exponentiation_mod(g, phi / p, n) != 1 % n)
+}
+
+fn phi_prime_factors(phi: u64) -> Vec<u64> {
+    AutoFactorizer::new().factor(phi).factors().iter().map(|&(p, _)| p).collect()
+}
+
+/// Smallest primitive root mod n
+///
+/// Only 1, 2, 4, p^k and 2p^k for an odd prime p have one, None is returned for any other n.
+/// Candidates are tested against the prime factors of φ(n), and the smallest primitive root
+/// is small in practice, so this costs about as much as factoring n and φ(n).
+/// ```
+/// use algebra::primitive_root::primitive_root;
+///
+/// assert_eq!(primitive_root(1), Some(0));
+/// assert_eq!(primitive_root(4), Some(3));
+/// assert_eq!(primitive_root(7), Some(3));
+/// assert_eq!(primitive_root(8), None);
+/// assert_eq!(primitive_root(2 * 49), Some(3));
+/// assert_eq!(primitive_root(998244353), Some(3));
+/// assert_eq!(primitive_root(18446744073709551557), Some(2));
+/// ```
+pub fn primitive_root(n: u64) -> Option<u64> {
+    if n <= 4 {
+        return [None, Some(0), Some(1), Some(2), Some(3)][n as usize];
+    }
+    let odd = if n.is_multiple_of(2) { n / 2 } else { n };
+    let factors = AutoFactorizer::new().factor(odd);
+    if odd.is_multiple_of(2) || factors.factors().len() != 1 {
+        return None;
+    }
+
+    let phi = euler_phi(n);
+    let primes = phi_prime_factors(phi);
+    (2..n).find(|&g| gcd(g, n) == 1 && is_generator(g, n, phi, &primes))
+}
+
+/// All x in [0, n) with x^k = a (mod n), in increasing order
+///
+/// n must have a primitive root g and a must be coprime to n, None is returned otherwise.
+/// With a = g^y, found by [`pohlig_hellman`], and x = g^z the equation becomes the linear
+/// congruence k z = y (mod φ(n)), which has gcd(k, φ(n)) solutions when the gcd divides y and none otherwise.
+///
+/// # Panics
+/// If k = 0, as x^0 = 1 holds for every x in [0, n) and not just the units
+/// ```
+/// use algebra::primitive_root::discrete_root;
+///
+/// assert_eq!(discrete_root(2, 2, 7), Some(vec![3, 4]));
+/// assert_eq!(discrete_root(2, 3, 7), Some(vec![]));
+/// assert_eq!(discrete_root(3, 1, 7), Some(vec![1, 2, 4]));
+/// assert_eq!(discrete_root(3, 8, 15), None);
+///
+/// let p = 1000000007;
+/// let roots = discrete_root(5, 123456789, p).unwrap();
+/// assert_eq!(roots.len(), 1);
+/// assert_eq!(algebra::binary_exponentiation::binary_exponentiation_mod(roots[0], 5, p), 123456789);
+/// ```
+pub fn discrete_root(k: u64, a: u64, n: u64) -> Option<Vec<u64>> {
+    assert!(k >= 1, "discrete roots need k >= 1");
+    let g = primitive_root(n)?;
+    let a = a % n;
+    if gcd(a, n) != 1 {
+        return None;
+    }
+    if n == 1 {
+        return Some(vec![0]);
+    }
+
+    let phi = euler_phi(n);
+    let y = pohlig_hellman(g, a, n).unwrap();
+    let d = gcd(k % phi, phi);
+    if !y.is_multiple_of(d) {
+        return Some(Vec::new());
+    }
+    // z = (k / d)^-1 * (y / d) mod (φ / d), plus multiples of φ / d
+    let step = phi / d;
+    let z0 = mul_mod(modular_inverse_u64(k / d, step).unwrap(), y / d, step);
+    let g_step = binary_exponentiation_mod(g, step, n);
+    let mut x = binary_exponentiation_mod(g, z0, n);
+    let mut roots = Vec::with_capacity(d as usize);
+    for _ in 0..d {
+        roots.push(x);
+        x = mul_mod(x, g_step, n);
+    }
+    roots.sort_unstable();
+
+    Some(roots)
+}
@@ -0,0 +1,533 @@
+//! [Quadratic Sieve](https://en.wikipedia.org/wiki/Quadratic_sieve)
+//!
+//! Self-initializing quadratic sieve. Relations (ax + b)^2 = a * g(x) (mod kn)
+//! come from sieving the polynomials g(x) = ax^2 + 2bx + c over [-M, M).
+//! Each a is a product of factor base primes, which gives 2^(s - 1) values of b that
+//! are switched between cheaply. Relations with one large prime left over are paired up,
+//! and a dependency among the relations over GF(2) yields X^2 = Y^2 (mod n).
+use num::{BigInt, Integer, One, Signed, ToPrimitive, Zero};
+use rand::{thread_rng, Rng};
+use std::collections::{HashMap, HashSet};
+
+use crate::binary_exponentiation::mul_mod;
+use crate::modular_inverse::modular_inverse_extended_gcd;
+use crate::modular_sqrt::{legendre, sqrt_mod};
+use crate::prime::primes;
+use crate::roots::{isqrt_bigint, perfect_power_bigint};
+
+/// Parameters for SIQS
+pub struct SiqsParams {
+    /// Number of primes in the factor base
+    pub factor_base_size: usize,
+    /// Half width M of the sieve interval [-M, M)
+    pub sieve_radius: usize,
+    /// Leftover cofactors below this multiple of the largest factor base prime
+    /// are kept as large primes
+    pub large_prime_multiplier: u64,
+}
+
+impl SiqsParams {
+    pub fn new(factor_base_size: usize, sieve_radius: usize, large_prime_multiplier: u64) -> SiqsParams {
+        SiqsParams { factor_base_size, sieve_radius, large_prime_multiplier }
+    }
+
+    /// Parameters suited to factoring an n with `digits` decimal digits
+    /// ```
+    /// let params = algebra::qs::SiqsParams::for_digits(40);
+    /// assert_eq!((params.factor_base_size, params.sieve_radius), (500, 65536));
+    /// ```
+    pub fn for_digits(digits: u32) -> SiqsParams {
+        let (factor_base_size, sieve_radius) = match digits {
+            0..=20 => (60, 16384),
+            21..=25 => (100, 16384),
+            26..=30 => (200, 32768),
+            31..=35 => (300, 32768),
+            36..=40 => (500, 65536),
+            41..=45 => (800, 65536),
+            46..=50 => (1200, 65536),
+            51..=55 => (1800, 98304),
+            56..=60 => (2500, 98304),
+            61..=65 => (3500, 131072),
+            66..=70 => (5000, 131072),
+            71..=75 => (7000, 196608),
+            _ => (9000, 196608),
+        };
+        SiqsParams::new(factor_base_size, sieve_radius, 64)
+    }
+}
+
+/// Self-initializing quadratic sieve with parameters picked from the size of n
+///
+/// Returns a non-trivial factor of n, or None if n is prime or every dependency was trivial.
+/// ```
+/// use num::BigInt;
+///
+/// let p: BigInt = "1000000000039".parse().unwrap();
+/// let q: BigInt = "10000000000000061".parse().unwrap();
+/// let d = algebra::qs::siqs(&(&p * &q)).unwrap();
+/// assert!(d == p || d == q);
+/// ```
+pub fn siqs(n: &BigInt) -> Option<BigInt> {
+    siqs_with(n, &SiqsParams::for_digits(n.to_string().len() as u32))
+}
+
+/// Self-initializing quadratic sieve with the given parameters
+///
+/// Same as [`siqs`], also returning None when the factor base is too small to supply
+/// enough distinct polynomials
+/// ```
+/// use num::BigInt;
+/// use algebra::qs::{siqs_with, SiqsParams};
+///
+/// let n: BigInt = "10000000000000061000000000000000000000000000000000000000003".parse().unwrap();
+/// assert_eq!(siqs_with(&n, &SiqsParams::new(5, 64, 1)), None);
+/// ```
+pub fn siqs_with(n: &BigInt, params: &SiqsParams) -> Option<BigInt> {
+    if n.is_even() {
+        return Some(BigInt::from(2));
+    }
+    if let Some((base, _)) = perfect_power_bigint(n) {
+        return Some(base);
+    }
+
+    let k = multiplier(n);
+    let kn: BigInt = n * k;
+    let factor_base = match FactorBase::new(n, &kn, params.factor_base_size) {
+        Ok(factor_base) => factor_base,
+        Err(d) => return Some(BigInt::from(d)),
+    };
+    let relations = Sieve::new(&kn, &factor_base, params).collect_relations()?;
+
+    for dependency in dependencies(&relations, factor_base.primes.len()) {
+        let mut x = BigInt::one();
+        let mut y = BigInt::one();
+        let mut exponents = vec![0u32; factor_base.primes.len()];
+        for &i in dependency.iter() {
+            let relation = &relations[i];
+            x = x * &relation.y % n;
+            y = y * &relation.large % n;
+            for &j in relation.factors.iter() {
+                exponents[j] += 1;
+            }
+        }
+        for (j, &e) in exponents.iter().enumerate() {
+            let p = BigInt::from(factor_base.primes[j].p);
+            y = y * p.modpow(&BigInt::from(e / 2), n) % n;
+        }
+        let d = (x - y).gcd(n);
+        if !d.is_one() && d != *n {
+            return Some(d);
+        }
+    }
+
+    None
+}
+
+/// Knuth Schroeppel multiplier k, the one giving kn the most small primes as quadratic residues
+fn multiplier(n: &BigInt) -> u64 {
+    const CANDIDATES: [u64; 19] = [1, 3, 5, 7, 11, 13, 15, 17, 19, 21, 23, 29, 31, 33, 35, 37, 39, 41, 43];
+    let small_primes = primes(1000);
+    let ln2 = 2f64.ln();
+    let mut best = (f64::MIN, 1);
+    for &k in CANDIDATES.iter() {
+        let kn: BigInt = n * k;
+        let mut score = -0.5 * (k as f64).ln();
+        score += match (&kn % 8u64).to_u64().unwrap() {
+            1 => 2.0 * ln2,
+            5 => ln2,
+            3 | 7 => 0.5 * ln2,
+            _ => 0.0,
+        };
+        for &p in small_primes.iter().skip(1) {
+            let p = p as u64;
+            let lnp = (p as f64).ln();
+            if k % p == 0 {
+                score += lnp / p as f64;
+            } else if legendre((&kn % p).to_u64().unwrap(), p) == 1 {
+                score += 2.0 * lnp / (p - 1) as f64;
+            }
+        }
+        if score > best.0 {
+            best = (score, k);
+        }
+    }
+
+    best.1
+}
+
+struct FactorBasePrime {
+    p: u64,
+    /// sqrt(kn) mod p
+    sqrt: u64,
+    /// log2(p) rounded
+    log: u8,
+}
+
+struct FactorBase {
+    primes: Vec<FactorBasePrime>,
+}
+
+/// Primes below this aren't sieved, their contribution is made up for in the threshold
+const SMALL_PRIME_BOUND: u64 = 50;
+
+impl FactorBase {
+    /// Primes p with kn a quadratic residue mod p, or a factor of n if one turns up
+    fn new(n: &BigInt, kn: &BigInt, size: usize) -> Result<FactorBase, u64> {
+        let mut fb = vec![FactorBasePrime { p: 2, sqrt: 1, log: 1 }];
+        let mut limit = 1000;
+        while fb.len() < size {
+            fb.truncate(1);
+            for p in primes(limit).into_iter().skip(1) {
+                let p = p as u64;
+                if (n % p).is_zero() {
+                    return Err(p);
+                }
+                let a = (kn % p).to_u64().unwrap();
+                if let Some(sqrt) = sqrt_mod(a, p) {
+                    let log = (p as f64).log2().round() as u8;
+                    fb.push(FactorBasePrime { p, sqrt, log });
+                    if fb.len() == size {
+                        break;
+                    }
+                }
+            }
+            limit *= 2;
+        }
+
+        Ok(FactorBase { primes: fb })
+    }
+}
+
+/// (ax + b)^2 = sign * large^2 * product of factor base primes (mod kn)
+struct Relation {
+    y: BigInt,
+    /// Factor base indices, with multiplicity
+    factors: Vec<usize>,
+    negative: bool,
+    /// Product of the large primes paired up in this relation, each appearing squared
+    large: BigInt,
+}
+
+struct Sieve<'a> {
+    kn: &'a BigInt,
+    fb: &'a FactorBase,
+    radius: usize,
+    large_prime_bound: u64,
+    threshold: u8,
+}
+
+impl<'a> Sieve<'a> {
+    fn new(kn: &'a BigInt, fb: &'a FactorBase, params: &SiqsParams) -> Sieve<'a> {
+        let p_max = fb.primes[fb.primes.len() - 1].p;
+        let large_prime_bound = p_max * params.large_prime_multiplier;
+        // g(x) is around M * sqrt(kn / 2) and may keep a large prime unsieved
+        let bits = kn.bits() as f64 / 2.0 + (params.sieve_radius as f64).log2() - 0.5;
+        let slack = (large_prime_bound as f64).log2() + 4.0;
+        Sieve {
+            kn,
+            fb,
+            radius: params.sieve_radius,
+            large_prime_bound,
+            threshold: (bits - slack).max(0.0) as u8,
+        }
+    }
+
+    /// Sieves polynomials until there are more relations than factor base primes
+    ///
+    /// None if the factor base runs out of fresh values of a first
+    fn collect_relations(&self) -> Option<Vec<Relation>> {
+        // Consecutive draws of an a already sieved before giving up
+        const MAX_REPEATS: usize = 1000;
+        let target = self.fb.primes.len() + 32;
+        let mut relations = Vec::new();
+        let mut partials: HashMap<u64, Relation> = HashMap::new();
+        let mut used_a = HashSet::new();
+        let mut repeats = 0;
+        while relations.len() < target {
+            let (a, q) = self.choose_a()?;
+            if !used_a.insert(q.clone()) {
+                repeats += 1;
+                if repeats == MAX_REPEATS {
+                    return None;
+                }
+                continue;
+            }
+            repeats = 0;
+            for relation in self.sieve_family(&a, &q) {
+                if relation.large.is_one() {
+                    relations.push(relation);
+                } else {
+                    let large = relation.large.to_u64().unwrap();
+                    match partials.remove(&large) {
+                        Some(other) => {
+                            let mut factors = relation.factors;
+                            factors.extend(other.factors);
+                            relations.push(Relation {
+                                y: relation.y * other.y % self.kn,
+                                factors,
+                                negative: relation.negative != other.negative,
+                                large: BigInt::from(large),
+                            });
+                        }
+                        None => {
+                            partials.insert(large, relation);
+                        }
+                    }
+                }
+            }
+        }
+
+        Some(relations)
+    }
+
+    /// a = q_1 * ... * q_s close to sqrt(2kn) / M, returned with the factor base indices of the q_i
+    ///
+    /// None if the factor base is too small to draw the q_i from
+    fn choose_a(&self) -> Option<(BigInt, Vec<usize>)> {
+        const MAX_ATTEMPTS: usize = 100;
+        let fb = &self.fb.primes;
+        let target: BigInt = isqrt_bigint(&(self.kn * 2u64)) / self.radius;
+        let target_log = target.bits() as f64;
+        // Draw the q_i from the middle of the factor base, above the small primes
+        let lo = fb.iter().position(|p| p.p > 2 * SMALL_PRIME_BOUND).unwrap_or(1).max(fb.len() / 3);
+        let hi = (2 * fb.len() / 3).max(lo + 2).min(fb.len());
+        let mid_log = (fb[(lo + hi) / 2].p as f64).log2();
+        let s = ((target_log / mid_log).round() as usize).max(2);
+        if hi < lo + s - 1 {
+            return None;
+        }
+
+        let mut rng = thread_rng();
+        for _ in 0..MAX_ATTEMPTS {
+            let mut q: Vec<usize> = Vec::new();
+            let mut a = BigInt::one();
+            while q.len() < s - 1 {
+                let i = rng.gen_range(lo, hi);
+                if !q.contains(&i) {
+                    q.push(i);
+                    a *= fb[i].p;
+                }
+            }
+            // The last prime brings a as close to the target as possible
+            let want = (&target / &a).to_u64().unwrap_or(u64::MAX);
+            let last = (1..fb.len())
+                .filter(|i| !q.contains(i) && fb[*i].p > SMALL_PRIME_BOUND)
+                .min_by_key(|&i| (fb[i].p as i128 - want as i128).abs());
+            if let Some(last) = last {
+                q.push(last);
+                a *= fb[last].p;
+                q.sort_unstable();
+                return Some((a, q));
+            }
+        }
+
+        None
+    }
+
+    /// Sieves all 2^(s - 1) polynomials sharing a
+    fn sieve_family(&self, a: &BigInt, q: &[usize]) -> Vec<Relation> {
+        let fb = &self.fb.primes;
+        let s = q.len();
+
+        // B_l = a / q_l * (sqrt(kn) * (a / q_l)^-1 mod q_l), so b = sum of B_l has b^2 = kn (mod a)
+        let big_b: Vec<BigInt> = q
+            .iter()
+            .map(|&l| {
+                let ql = fb[l].p;
+                let a_l = a / ql;
+                let a_l_inv = inverse((&a_l % ql).to_u64().unwrap(), ql);
+                let mut gamma = mul_mod(fb[l].sqrt, a_l_inv, ql);
+                if gamma > ql / 2 {
+                    gamma = ql - gamma;
+                }
+                a_l * gamma
+            })
+            .collect();
+        let mut b: BigInt = big_b.iter().sum();
+
+        // Roots of g mod p as positions in the sieve array, x = i - M,
+        // and how they move when b changes by 2 B_l
+        let m = self.radius as u64;
+        let mut roots: Vec<Option<(u64, u64)>> = Vec::with_capacity(fb.len());
+        let mut deltas: Vec<Vec<u64>> = vec![Vec::with_capacity(fb.len()); s];
+        for (j, fp) in fb.iter().enumerate() {
+            let p = fp.p;
+            if p < SMALL_PRIME_BOUND || q.contains(&j) {
+                roots.push(None);
+                for delta in deltas.iter_mut() {
+                    delta.push(0);
+                }
+                continue;
+            }
+            let a_inv = inverse((a % p).to_u64().unwrap(), p);
+            let b_p = (&b % p).to_u64().unwrap();
+            let r1 = mul_mod(a_inv, (fp.sqrt + p - b_p) % p, p);
+            let r2 = mul_mod(a_inv, (2 * p - fp.sqrt - b_p) % p, p);
+            roots.push(Some(((r1 + m) % p, (r2 + m) % p)));
+            for (l, delta) in deltas.iter_mut().enumerate() {
+                let bl = (&big_b[l] % p).to_u64().unwrap();
+                delta.push(mul_mod(2 * bl % p, a_inv, p));
+            }
+        }
+
+        let mut relations = Vec::new();
+        let mut sieve = vec![0u8; 2 * self.radius];
+        for i in 0..(1u64 << (s - 1)) {
+            if i > 0 {
+                // Gray code, flip the sign of B_l for l = v + 1
+                let v = i.trailing_zeros() as usize;
+                let l = v + 1;
+                let plus = (i >> (v + 1)) & 1 == 1;
+                if plus {
+                    b += 2 * &big_b[l];
+                } else {
+                    b -= 2 * &big_b[l];
+                }
+                for (j, root) in roots.iter_mut().enumerate() {
+                    if let Some((r1, r2)) = root {
+                        let p = fb[j].p;
+                        let d = deltas[l][j];
+                        let shift = if plus { p - d } else { d };
+                        *r1 = (*r1 + shift) % p;
+                        *r2 = (*r2 + shift) % p;
+                    }
+                }
+            }
+
+            for v in sieve.iter_mut() {
+                *v = 0;
+            }
+            for (j, root) in roots.iter().enumerate() {
+                if let Some((r1, r2)) = *root {
+                    let p = fb[j].p as usize;
+                    let log = fb[j].log;
+                    let mut k = r1 as usize;
+                    while k < sieve.len() {
+                        sieve[k] = sieve[k].wrapping_add(log);
+                        k += p;
+                    }
+                    if r2 != r1 {
+                        let mut k = r2 as usize;
+                        while k < sieve.len() {
+                            sieve[k] = sieve[k].wrapping_add(log);
+                            k += p;
+                        }
+                    }
+                }
+            }
+
+            let c: BigInt = (&b * &b - self.kn) / a;
+            for (pos, &v) in sieve.iter().enumerate() {
+                if v >= self.threshold {
+                    let x = BigInt::from(pos as i64 - self.radius as i64);
+                    if let Some(relation) = self.check(a, &b, &c, &x, pos as u64, q, &roots) {
+                        relations.push(relation);
+                    }
+                }
+            }
+        }
+
+        relations
+    }
+
+    /// Trial divides g(x), keeping the relation if it splits over the factor base and one large prime
+    #[allow(clippy::too_many_arguments)]
+    fn check(
+        &self,
+        a: &BigInt,
+        b: &BigInt,
+        c: &BigInt,
+        x: &BigInt,
+        pos: u64,
+        q: &[usize],
+        roots: &[Option<(u64, u64)>],
+    ) -> Option<Relation> {
+        let g: BigInt = (a * x + 2 * b) * x + c;
+        if g.is_zero() {
+            return None;
+        }
+        let negative = g.is_negative();
+        let mut rest = g.abs();
+        let mut factors: Vec<usize> = q.to_vec();
+        for (j, fp) in self.fb.primes.iter().enumerate() {
+            let divides = match roots[j] {
+                Some((r1, r2)) => pos % fp.p == r1 || pos % fp.p == r2,
+                None => (&rest % fp.p).is_zero(),
+            };
+            if divides {
+                while (&rest % fp.p).is_zero() {
+                    rest /= fp.p;
+                    factors.push(j);
+                }
+            }
+        }
+
+        let large = rest.to_u64()?;
+        if large != 1 && large >= self.large_prime_bound {
+            return None;
+        }
+        Some(Relation {
+            y: a * x + b,
+            factors,
+            negative,
+            large: BigInt::from(large),
+        })
+    }
+}
+
+/// a^-1 mod p
+fn inverse(a: u64, p: u64) -> u64 {
+    modular_inverse_extended_gcd(a as i64, p as i64).unwrap() as u64
+}
+
+/// Subsets of relations whose exponent vectors sum to zero over GF(2)
+///
+/// Gaussian elimination on the exponent parities, with each row carrying
+/// the set of relations it's made of
+fn dependencies(relations: &[Relation], columns: usize) -> Vec<Vec<usize>> {
+    let words = |bits: usize| bits.div_ceil(64);
+    // Column 0 is the sign, column j + 1 is factor base prime j
+    let width = words(columns + 1);
+    let history_width = words(relations.len());
+    let mut rows: Vec<(Vec<u64>, Vec<u64>)> = relations
+        .iter()
+        .enumerate()
+        .map(|(i, relation)| {
+            let mut row = vec![0u64; width];
+            if relation.negative {
+                row[0] ^= 1;
+            }
+            for &j in relation.factors.iter() {
+                row[(j + 1) / 64] ^= 1 << ((j + 1) % 64);
+            }
+            let mut history = vec![0u64; history_width];
+            history[i / 64] |= 1 << (i % 64);
+            (row, history)
+        })
+        .collect();
+
+    let mut pivot_used = vec![false; rows.len()];
+    for col in 0..=columns {
+        let (w, bit) = (col / 64, 1u64 << (col % 64));
+        let pivot = (0..rows.len()).find(|&r| !pivot_used[r] && rows[r].0[w] & bit != 0);
+        if let Some(pivot) = pivot {
+            pivot_used[pivot] = true;
+            let (pivot_row, pivot_history) = rows[pivot].clone();
+            for (r, row) in rows.iter_mut().enumerate() {
+                if r != pivot && row.0[w] & bit != 0 {
+                    for (x, y) in row.0.iter_mut().zip(pivot_row.iter()) {
+                        *x ^= y;
+                    }
+                    for (x, y) in row.1.iter_mut().zip(pivot_history.iter()) {
+                        *x ^= y;
+                    }
+                }
+            }
+        }
+    }
+
+    rows.into_iter()
+        .filter(|(row, _)| row.iter().all(|&w| w == 0))
+        .map(|(_, history)| (0..relations.len()).filter(|&i| history[i / 64] >> (i % 64) & 1 == 1).collect())
+        .collect()
+}
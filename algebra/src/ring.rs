@@ -0,0 +1,159 @@
+//! Modular arithmetic shared by the factorization methods
+//!
+//! Each method is written once against [`Ring`] and runs over big integers,
+//! or over 128 bit integers with Montgomery multiplication.
+use num::{BigInt, Integer, One, Zero};
+
+/// Arithmetic modulo the number being factored
+pub(crate) trait Ring {
+    type Elem: Clone;
+    type Int;
+    fn elem(&self, x: u64) -> Self::Elem;
+    fn add(&self, a: &Self::Elem, b: &Self::Elem) -> Self::Elem;
+    fn sub(&self, a: &Self::Elem, b: &Self::Elem) -> Self::Elem;
+    fn mul(&self, a: &Self::Elem, b: &Self::Elem) -> Self::Elem;
+    /// a = 0 mod n, where gcd(a, n) = n says nothing about the factors
+    fn is_zero(&self, a: &Self::Elem) -> bool;
+    /// gcd(a, n) if it's a non-trivial factor of n
+    fn factor(&self, a: &Self::Elem) -> Option<Self::Int>;
+}
+
+pub(crate) struct BigRing {
+    n: BigInt,
+}
+
+impl BigRing {
+    pub(crate) fn new(n: &BigInt) -> BigRing {
+        BigRing { n: n.clone() }
+    }
+}
+
+impl Ring for BigRing {
+    type Elem = BigInt;
+    type Int = BigInt;
+
+    fn elem(&self, x: u64) -> BigInt {
+        BigInt::from(x) % &self.n
+    }
+
+    fn add(&self, a: &BigInt, b: &BigInt) -> BigInt {
+        let s = a + b;
+        if s >= self.n { s - &self.n } else { s }
+    }
+
+    fn sub(&self, a: &BigInt, b: &BigInt) -> BigInt {
+        if a >= b { a - b } else { a + &self.n - b }
+    }
+
+    fn mul(&self, a: &BigInt, b: &BigInt) -> BigInt {
+        a * b % &self.n
+    }
+
+    fn is_zero(&self, a: &BigInt) -> bool {
+        a.is_zero()
+    }
+
+    fn factor(&self, a: &BigInt) -> Option<BigInt> {
+        let g = a.gcd(&self.n);
+        if g.is_one() || g == self.n { None } else { Some(g) }
+    }
+}
+
+/// Odd modulus below 2^128 with elements kept in Montgomery form, x * 2^128 mod n
+pub(crate) struct MontgomeryRing {
+    n: u128,
+    /// -n^-1 mod 2^128
+    n_inv: u128,
+    /// 2^256 mod n
+    r2: u128,
+}
+
+/// Full 256 bit product of a and b as (high, low)
+fn mul_wide(a: u128, b: u128) -> (u128, u128) {
+    const MASK: u128 = (1 << 64) - 1;
+    let (a1, a0) = (a >> 64, a & MASK);
+    let (b1, b0) = (b >> 64, b & MASK);
+    let lo = a0 * b0;
+    let mid1 = a1 * b0;
+    let mid2 = a0 * b1;
+    let hi = a1 * b1;
+    let mid = (lo >> 64) + (mid1 & MASK) + (mid2 & MASK);
+
+    (hi + (mid1 >> 64) + (mid2 >> 64) + (mid >> 64), (mid << 64) | (lo & MASK))
+}
+
+impl MontgomeryRing {
+    pub(crate) fn new(n: u128) -> MontgomeryRing {
+        // Newton's iteration doubles the correct low bits, n * n = 1 mod 8 to start
+        let mut inv = n;
+        for _ in 0..7 {
+            inv = inv.wrapping_mul(2u128.wrapping_sub(n.wrapping_mul(inv)));
+        }
+        let mut r2 = (u128::MAX % n + 1) % n;
+        for _ in 0..128 {
+            r2 = add_mod(r2, r2, n);
+        }
+
+        MontgomeryRing { n, n_inv: inv.wrapping_neg(), r2 }
+    }
+
+    /// Montgomery reduction, (hi * 2^128 + lo) / 2^128 mod n
+    fn reduce(&self, (hi, lo): (u128, u128)) -> u128 {
+        let m = lo.wrapping_mul(self.n_inv);
+        let (mh, ml) = mul_wide(m, self.n);
+        // lo + ml is either 0 or exactly 2^128
+        let carry = (lo != 0 || ml != 0) as u128;
+        let (t, overflow1) = hi.overflowing_add(mh);
+        let (t, overflow2) = t.overflowing_add(carry);
+        if overflow1 || overflow2 || t >= self.n { t.wrapping_sub(self.n) } else { t }
+    }
+}
+
+fn add_mod(a: u128, b: u128, n: u128) -> u128 {
+    let (s, overflow) = a.overflowing_add(b);
+    if overflow || s >= n { s.wrapping_sub(n) } else { s }
+}
+
+impl Ring for MontgomeryRing {
+    type Elem = u128;
+    type Int = u128;
+
+    fn elem(&self, x: u64) -> u128 {
+        self.reduce(mul_wide(x as u128 % self.n, self.r2))
+    }
+
+    fn add(&self, a: &u128, b: &u128) -> u128 {
+        add_mod(*a, *b, self.n)
+    }
+
+    fn sub(&self, a: &u128, b: &u128) -> u128 {
+        if a >= b { a - b } else { a.wrapping_sub(*b).wrapping_add(self.n) }
+    }
+
+    fn mul(&self, a: &u128, b: &u128) -> u128 {
+        self.reduce(mul_wide(*a, *b))
+    }
+
+    fn is_zero(&self, a: &u128) -> bool {
+        *a == 0
+    }
+
+    fn factor(&self, a: &u128) -> Option<u128> {
+        // 2^128 is coprime to n, so the Montgomery form has the same gcd
+        let g = a.gcd(&self.n);
+        if g == 1 || g == self.n { None } else { Some(g) }
+    }
+}
+
+/// a^k for k >= 1
+pub(crate) fn pow<R: Ring>(ring: &R, a: &R::Elem, k: u64) -> R::Elem {
+    let mut res = a.clone();
+    for bit in (0..63 - k.leading_zeros()).rev() {
+        res = ring.mul(&res, &res);
+        if (k >> bit) & 1 == 1 {
+            res = ring.mul(&res, a);
+        }
+    }
+
+    res
+}
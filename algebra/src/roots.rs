@@ -0,0 +1,310 @@
+//! [Integer Roots](https://en.wikipedia.org/wiki/Integer_square_root)
+//!
+//! Floating point gives a close first guess, which Newton's method then corrects
+//! so every result is exact for the whole range of the type.
+use num::{BigInt, One, Signed, ToPrimitive};
+
+/// Integer square root, floor(sqrt(n))
+/// ```
+/// assert_eq!(algebra::roots::isqrt(99), 9);
+/// assert_eq!(algebra::roots::isqrt(100), 10);
+/// assert_eq!(algebra::roots::isqrt(18446744073709551615), 4294967295);
+/// ```
+pub fn isqrt(n: u64) -> u64 {
+    let n128 = n as u128;
+    let mut r = (n as f64).sqrt() as u128;
+    while r * r > n128 {
+        r -= 1;
+    }
+    while (r + 1) * (r + 1) <= n128 {
+        r += 1;
+    }
+
+    r as u64
+}
+
+/// Integer square root for 128 bit integers
+/// ```
+/// assert_eq!(algebra::roots::isqrt_u128(u128::MAX), u64::MAX as u128);
+/// assert_eq!(algebra::roots::isqrt_u128(1 << 100), 1 << 50);
+/// assert_eq!(algebra::roots::isqrt_u128((1 << 100) - 1), (1 << 50) - 1);
+/// ```
+pub fn isqrt_u128(n: u128) -> u128 {
+    if n < 1 << 64 {
+        return isqrt(n as u64) as u128;
+    }
+    iroot_u128(n, 2)
+}
+
+/// Integer square root of a non-negative big integer
+/// ```
+/// use num::BigInt;
+///
+/// let n: BigInt = "100000000000000000000000000000000000000000".parse().unwrap();
+/// assert_eq!(algebra::roots::isqrt_bigint(&n), "316227766016837933199".parse().unwrap());
+/// ```
+pub fn isqrt_bigint(n: &BigInt) -> BigInt {
+    n.sqrt()
+}
+
+/// Integer cube root, floor(cbrt(n))
+/// ```
+/// assert_eq!(algebra::roots::icbrt(26), 2);
+/// assert_eq!(algebra::roots::icbrt(27), 3);
+/// assert_eq!(algebra::roots::icbrt(18446744073709551615), 2642245);
+/// ```
+pub fn icbrt(n: u64) -> u64 {
+    iroot(n, 3)
+}
+
+/// Integer cube root for 128 bit integers
+/// ```
+/// assert_eq!(algebra::roots::icbrt_u128(u128::MAX), 6981463658331);
+/// ```
+pub fn icbrt_u128(n: u128) -> u128 {
+    iroot_u128(n, 3)
+}
+
+/// Integer cube root of a big integer
+/// ```
+/// use num::BigInt;
+///
+/// let n = num::pow(BigInt::from(1000000007_u64), 3);
+/// assert_eq!(algebra::roots::icbrt_bigint(&n), BigInt::from(1000000007_u64));
+/// assert_eq!(algebra::roots::icbrt_bigint(&(n - 1)), BigInt::from(1000000006_u64));
+/// ```
+pub fn icbrt_bigint(n: &BigInt) -> BigInt {
+    n.cbrt()
+}
+
+/// Integer k-th root, floor(n^(1/k)) for k >= 1
+/// ```
+/// assert_eq!(algebra::roots::iroot(1 << 60, 5), 1 << 12);
+/// assert_eq!(algebra::roots::iroot((1 << 60) - 1, 5), (1 << 12) - 1);
+/// assert_eq!(algebra::roots::iroot(18446744073709551615, 64), 1);
+/// ```
+pub fn iroot(n: u64, k: u32) -> u64 {
+    iroot_u128(n as u128, k) as u64
+}
+
+/// Integer k-th root for 128 bit integers
+///
+/// Starts Newton's method from a floating point guess.
+/// One step from anywhere lands on or above the root, after which the iterates decrease to it.
+/// ```
+/// assert_eq!(algebra::roots::iroot_u128(u128::MAX, 2), u64::MAX as u128);
+/// assert_eq!(algebra::roots::iroot_u128(3_u128.pow(80), 5), 3_u128.pow(16));
+/// assert_eq!(algebra::roots::iroot_u128(3_u128.pow(80) - 1, 5), 3_u128.pow(16) - 1);
+/// ```
+pub fn iroot_u128(n: u128, k: u32) -> u128 {
+    assert!(k >= 1, "iroot_u128: k must be at least 1");
+    if k == 1 || n < 2 {
+        return n;
+    }
+    if k >= 128 {
+        return 1;
+    }
+
+    let k128 = k as u128;
+    let step = |x: u128| {
+        let below = x.checked_pow(k - 1).map_or(0, |xk| n / xk);
+        ((k128 - 1) * x + below) / k128
+    };
+    let guess = ((n as f64).powf(1.0 / k as f64) as u128).max(1);
+    let mut x = step(guess);
+    loop {
+        let y = step(x);
+        if y >= x {
+            return x;
+        }
+        x = y;
+    }
+}
+
+/// Integer k-th root of a big integer
+/// ```
+/// use num::BigInt;
+///
+/// let n = num::pow(BigInt::from(12345_u64), 11);
+/// assert_eq!(algebra::roots::iroot_bigint(&n, 11), BigInt::from(12345));
+/// assert_eq!(algebra::roots::iroot_bigint(&(n - 1), 11), BigInt::from(12344));
+/// ```
+pub fn iroot_bigint(n: &BigInt, k: u32) -> BigInt {
+    n.nth_root(k)
+}
+
+/// Bit i of SQUARES_MOD_m is set when i is a square mod m
+const fn square_mask(m: u64) -> u128 {
+    let mut mask = 0;
+    let mut i = 0;
+    while i < m {
+        mask |= 1 << (i * i % m);
+        i += 1;
+    }
+
+    mask
+}
+
+const SQUARES_MOD_64: u128 = square_mask(64);
+const SQUARES_MOD_63: u128 = square_mask(63);
+const SQUARES_MOD_65: u128 = square_mask(65);
+const SQUARES_MOD_11: u128 = square_mask(11);
+
+/// Rejects all but about 1 in 150 non-squares by their residues mod 64, 63, 65 and 11
+fn maybe_square(r64: u64, r63: u64, r65: u64, r11: u64) -> bool {
+    SQUARES_MOD_64 >> r64 & 1 == 1
+        && SQUARES_MOD_63 >> r63 & 1 == 1
+        && SQUARES_MOD_65 >> r65 & 1 == 1
+        && SQUARES_MOD_11 >> r11 & 1 == 1
+}
+
+/// Checks whether n is a perfect square
+///
+/// Quadratic residue filters discard most non-squares before any root is taken
+/// ```
+/// assert!(algebra::roots::is_perfect_square(0));
+/// assert!(algebra::roots::is_perfect_square(4294967295 * 4294967295));
+/// assert!(!algebra::roots::is_perfect_square(4294967295 * 4294967295 - 1));
+/// ```
+pub fn is_perfect_square(n: u64) -> bool {
+    if !maybe_square(n % 64, n % 63, n % 65, n % 11) {
+        return false;
+    }
+    let r = isqrt(n);
+
+    r * r == n
+}
+
+/// Checks whether a 128 bit integer is a perfect square
+/// ```
+/// assert!(algebra::roots::is_perfect_square_u128(u64::MAX as u128 * u64::MAX as u128));
+/// assert!(!algebra::roots::is_perfect_square_u128(u128::MAX));
+/// ```
+pub fn is_perfect_square_u128(n: u128) -> bool {
+    let (r64, r63, r65, r11) = (n % 64, n % 63, n % 65, n % 11);
+    if !maybe_square(r64 as u64, r63 as u64, r65 as u64, r11 as u64) {
+        return false;
+    }
+    let r = isqrt_u128(n);
+
+    r * r == n
+}
+
+/// Checks whether a big integer is a perfect square, false for negative n
+/// ```
+/// use num::BigInt;
+///
+/// let n = num::pow(BigInt::from(1000000007_u64), 4);
+/// assert!(algebra::roots::is_perfect_square_bigint(&n));
+/// assert!(!algebra::roots::is_perfect_square_bigint(&(n + 1)));
+/// assert!(!algebra::roots::is_perfect_square_bigint(&BigInt::from(-4)));
+/// ```
+pub fn is_perfect_square_bigint(n: &BigInt) -> bool {
+    if n.is_negative() {
+        return false;
+    }
+    // 64 * 63 * 65 * 11
+    let r = (n % 2882880_u64).to_u64().unwrap();
+    if !maybe_square(r % 64, r % 63, r % 65, r % 11) {
+        return false;
+    }
+    let root = n.sqrt();
+
+    &root * &root == *n
+}
+
+/// Prime exponents worth trying for a number with the given bit length
+fn prime_exponents(bits: u32) -> impl Iterator<Item = u32> {
+    (2..=bits).filter(|&k| (2..k).take_while(|d| d * d <= k).all(|d| !k.is_multiple_of(d)))
+}
+
+/// Perfect power detection
+///
+/// Returns (b, e) with n = b^e for the largest e >= 2, or None if n isn't a perfect power.
+/// 0 and 1 aren't considered perfect powers.
+/// ```
+/// assert_eq!(algebra::roots::perfect_power(64), Some((2, 6)));
+/// assert_eq!(algebra::roots::perfect_power(1000000007 * 1000000007), Some((1000000007, 2)));
+/// assert_eq!(algebra::roots::perfect_power(3_u64.pow(40)), Some((3, 40)));
+/// assert_eq!(algebra::roots::perfect_power(72), None);
+/// assert_eq!(algebra::roots::perfect_power(1), None);
+/// ```
+pub fn perfect_power(n: u64) -> Option<(u64, u32)> {
+    perfect_power_u128(n as u128).map(|(b, e)| (b as u64, e))
+}
+
+/// Perfect power detection for 128 bit integers
+///
+/// Same as [`perfect_power`]
+/// ```
+/// assert_eq!(algebra::roots::perfect_power_u128(1 << 126), Some((2, 126)));
+/// assert_eq!(algebra::roots::perfect_power_u128(6_u128.pow(42)), Some((6, 42)));
+/// assert_eq!(algebra::roots::perfect_power_u128(u128::MAX), None);
+/// ```
+pub fn perfect_power_u128(n: u128) -> Option<(u128, u32)> {
+    if n < 2 {
+        return None;
+    }
+
+    // Peel off prime exponents one at a time, b^(pq) = (b^p)^q
+    let (mut base, mut exp) = (n, 1);
+    'outer: loop {
+        for k in prime_exponents(128 - base.leading_zeros()) {
+            if k == 2 && !is_perfect_square_u128(base) {
+                continue;
+            }
+            let r = iroot_u128(base, k);
+            if r.pow(k) == base {
+                base = r;
+                exp *= k;
+                continue 'outer;
+            }
+        }
+        break;
+    }
+
+    if exp == 1 {
+        None
+    } else {
+        Some((base, exp))
+    }
+}
+
+/// Perfect power detection for big integers
+///
+/// Same as [`perfect_power`], None for negative n
+/// ```
+/// use num::BigInt;
+///
+/// let b = BigInt::from(1000000007_u64);
+/// let n = num::pow(b.clone(), 6);
+/// assert_eq!(algebra::roots::perfect_power_bigint(&n), Some((b, 6)));
+/// assert_eq!(algebra::roots::perfect_power_bigint(&(n + 1)), None);
+/// ```
+pub fn perfect_power_bigint(n: &BigInt) -> Option<(BigInt, u32)> {
+    if n <= &BigInt::one() {
+        return None;
+    }
+
+    let (mut base, mut exp) = (n.clone(), 1);
+    'outer: loop {
+        for k in prime_exponents(base.bits() as u32) {
+            if k == 2 && !is_perfect_square_bigint(&base) {
+                continue;
+            }
+            let r = base.nth_root(k);
+            if num::pow(r.clone(), k as usize) == base {
+                base = r;
+                exp *= k;
+                continue 'outer;
+            }
+        }
+        break;
+    }
+
+    if exp == 1 {
+        None
+    } else {
+        Some((base, exp))
+    }
+}
@@ -0,0 +1,97 @@
+//! [Euler's Totient](https://cp-algorithms.com/algebra/phi-function.html), Carmichael function
+//! and multiplicative order for 64-bit moduli
+use crate::binary_exponentiation::binary_exponentiation_mod;
+use crate::factorizer::{AutoFactorizer, Factorizer};
+use crate::gcd::gcd;
+use crate::lcm::lcm;
+
+/// Euler's totient φ(n), the number of integers in [1, n] coprime to n
+///
+/// φ(0) is taken to be 0.
+/// ```
+/// use algebra::totient::euler_phi;
+///
+/// assert_eq!(euler_phi(1), 1);
+/// assert_eq!(euler_phi(36), 12);
+/// assert_eq!(euler_phi(1000000007), 1000000006);
+/// assert_eq!(euler_phi(18446744073709551615), 9208981628670443520);
+/// ```
+pub fn euler_phi(n: u64) -> u64 {
+    if n == 0 {
+        return 0;
+    }
+
+    AutoFactorizer::new()
+        .factor(n)
+        .factors()
+        .iter()
+        .fold(n, |phi, &(p, _)| phi / p * (p - 1))
+}
+
+/// Carmichael function λ(n), the exponent of the multiplicative group mod n
+///
+/// a^λ(n) = 1 (mod n) for every a coprime to n, and λ(n) is the least such exponent.
+/// λ(0) is taken to be 0.
+/// ```
+/// use algebra::totient::carmichael_lambda;
+///
+/// assert_eq!(carmichael_lambda(1), 1);
+/// assert_eq!(carmichael_lambda(8), 2);
+/// assert_eq!(carmichael_lambda(15), 4);
+/// assert_eq!(carmichael_lambda(561), 80);
+/// assert_eq!(carmichael_lambda(1 << 63), 1 << 61);
+/// ```
+pub fn carmichael_lambda(n: u64) -> u64 {
+    if n == 0 {
+        return 0;
+    }
+
+    AutoFactorizer::new()
+        .factor(n)
+        .factors()
+        .iter()
+        .map(|&(p, k)| carmichael_lambda_prime_power(p, k))
+        .fold(1, lcm)
+}
+
+/// λ(p^k)
+fn carmichael_lambda_prime_power(p: u64, k: u32) -> u64 {
+    if p == 2 && k >= 3 {
+        1 << (k - 2)
+    } else {
+        (p - 1) * p.pow(k - 1)
+    }
+}
+
+/// Multiplicative order of a mod n, the least t >= 1 with a^t = 1 (mod n)
+///
+/// None when a isn't coprime to n or n = 0.
+/// The order divides λ(n), so prime factors are stripped from λ(n) for as long as
+/// a^t stays 1, which takes O(log^2 n) multiplications once λ(n) is factored.
+/// ```
+/// use algebra::totient::multiplicative_order;
+///
+/// assert_eq!(multiplicative_order(2, 7), Some(3));
+/// assert_eq!(multiplicative_order(3, 7), Some(6));
+/// assert_eq!(multiplicative_order(4, 1), Some(1));
+/// assert_eq!(multiplicative_order(6, 9), None);
+/// assert_eq!(multiplicative_order(10, 1000000007), Some(1000000006));
+///
+/// // 2 is a primitive root of this prime
+/// let p = 18446744073709551557;
+/// assert_eq!(multiplicative_order(2, p), Some(p - 1));
+/// ```
+pub fn multiplicative_order(a: u64, n: u64) -> Option<u64> {
+    if n == 0 || gcd(a % n, n) != 1 {
+        return None;
+    }
+
+    let mut t = carmichael_lambda(n);
+    for &(p, _) in AutoFactorizer::new().factor(t).factors() {
+        while t.is_multiple_of(p) && binary_exponentiation_mod(a, t / p, n) == 1 {
+            t /= p;
+        }
+    }
+
+    Some(t)
+}